@@ -0,0 +1,355 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::Error;
+
+// A CIM DATETIME value (DMTF DSP0004), the string format WMI uses for every date and duration:
+//   timestamp  yyyymmddHHMMSS.mmmmmmsUUU  e.g. 20240105083012.500000+060
+//   interval   ddddddddHHMMSS.mmmmmm:000  e.g. 00000001020304.000000:000
+// Any field may be replaced by asterisks when it isn't significant, and trailing microsecond
+// digits may be asterisks to express a lower precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CimDateTime {
+    Timestamp(CimTimestamp),
+    Interval(CimInterval),
+}
+
+// Wildcarded fields are None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CimTimestamp {
+    pub year: Option<u16>,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    pub hour: Option<u8>,
+    pub minute: Option<u8>,
+    pub second: Option<u8>,
+    pub microsecond: Microseconds,
+    pub offset_minutes: i16, // Offset of the local time from UTC, e.g. 60 for CET
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CimInterval {
+    pub days: Option<u32>,
+    pub hours: Option<u8>,
+    pub minutes: Option<u8>,
+    pub seconds: Option<u8>,
+    pub microseconds: Microseconds,
+}
+
+// The microsecond field and how many of its six digits are significant (0 when fully wildcarded)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Microseconds {
+    pub value: u32,
+    pub precision: u8,
+}
+
+impl CimDateTime {
+    pub fn parse(value: &str) -> Result<CimDateTime, Error> {
+        let error = |reason: &str| Error::parse("CIM_DATETIME", format!("'{}': {}", value, reason));
+        if value.len() != 25 || !value.is_ascii() {
+            return Err(error("expected 25 ASCII characters"));
+        }
+        if &value[14..15] != "." {
+            return Err(error("expected '.' before the microseconds"));
+        }
+        let field = |range: std::ops::Range<usize>| -> Result<Option<u32>, Error> {
+            let digits = &value[range];
+            if digits.bytes().all(|b| b == b'*') {
+                Ok(None)
+            } else if digits.bytes().all(|b| b.is_ascii_digit()) {
+                Ok(digits.parse().ok())
+            } else {
+                Err(error("fields must be all digits or all asterisks"))
+            }
+        };
+        let microsecond = Microseconds::parse(&value[15..21]).ok_or_else(|| error("malformed microseconds"))?;
+        let check = |field: Option<u32>, range: std::ops::RangeInclusive<u32>, name: &str| match field {
+            Some(n) if !range.contains(&n) => Err(error(&format!("{} out of range", name))),
+            _ => Ok(field),
+        };
+
+        match &value[21..22] {
+            ":" => {
+                if &value[22..] != "000" {
+                    return Err(error("intervals must end in ':000'"));
+                }
+                Ok(CimDateTime::Interval(CimInterval {
+                    days: field(0..8)?,
+                    hours: check(field(8..10)?, 0..=23, "hours")?.map(|n| n as u8),
+                    minutes: check(field(10..12)?, 0..=59, "minutes")?.map(|n| n as u8),
+                    seconds: check(field(12..14)?, 0..=59, "seconds")?.map(|n| n as u8),
+                    microseconds: microsecond,
+                }))
+            }
+            sign @ ("+" | "-") => {
+                let offset = field(22..25)?.ok_or_else(|| error("the UTC offset can't be a wildcard"))? as i16;
+                Ok(CimDateTime::Timestamp(CimTimestamp {
+                    year: field(0..4)?.map(|n| n as u16),
+                    month: check(field(4..6)?, 1..=12, "month")?.map(|n| n as u8),
+                    day: check(field(6..8)?, 1..=31, "day")?.map(|n| n as u8),
+                    hour: check(field(8..10)?, 0..=23, "hour")?.map(|n| n as u8),
+                    minute: check(field(10..12)?, 0..=59, "minute")?.map(|n| n as u8),
+                    // 60 allows for leap seconds
+                    second: check(field(12..14)?, 0..=60, "second")?.map(|n| n as u8),
+                    microsecond,
+                    offset_minutes: if sign == "-" { -offset } else { offset },
+                }))
+            }
+            _ => Err(error("expected a UTC offset or ':000'")),
+        }
+    }
+
+    pub fn as_timestamp(&self) -> Option<&CimTimestamp> {
+        match self {
+            CimDateTime::Timestamp(timestamp) => Some(timestamp),
+            CimDateTime::Interval(_) => None,
+        }
+    }
+
+    pub fn as_interval(&self) -> Option<&CimInterval> {
+        match self {
+            CimDateTime::Interval(interval) => Some(interval),
+            CimDateTime::Timestamp(_) => None,
+        }
+    }
+}
+
+impl CimTimestamp {
+    // The point in time, as long as the date and time down to the second are all present.
+    // Wildcarded microseconds count as zero. A leap second (60) becomes chrono's representation,
+    // second 59 with an extra second of microseconds.
+    pub fn to_datetime(&self) -> Option<DateTime<FixedOffset>> {
+        let offset = FixedOffset::east_opt(self.offset_minutes as i32 * 60)?;
+        let date = NaiveDate::from_ymd_opt(self.year? as i32, self.month? as u32, self.day? as u32)?;
+        let (second, microsecond) = match self.second? {
+            60 => (59, self.microsecond.value + 1_000_000),
+            second => (second as u32, self.microsecond.value),
+        };
+        let time = date.and_hms_micro_opt(self.hour? as u32, self.minute? as u32, second, microsecond)?;
+        offset.from_local_datetime(&time).single()
+    }
+
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        self.to_datetime().map(|time| time.with_timezone(&Utc))
+    }
+}
+
+// Fails for years outside 0..=9999 and offsets beyond 999 minutes, which don't fit the
+// fixed-width fields
+impl<Tz: TimeZone> TryFrom<DateTime<Tz>> for CimTimestamp {
+    type Error = Error;
+
+    fn try_from(time: DateTime<Tz>) -> Result<Self, Self::Error> {
+        let time = time.fixed_offset();
+        let error = |reason: String| Error::parse("CIM_DATETIME", format!("'{}': {}", time.to_rfc3339(), reason));
+        let year = u16::try_from(time.year())
+            .ok()
+            .filter(|&year| year <= 9999)
+            .ok_or_else(|| error(format!("year {} doesn't fit in four digits", time.year())))?;
+        let offset_minutes = time.offset().local_minus_utc() / 60;
+        if offset_minutes.abs() > 999 {
+            return Err(error(format!("UTC offset of {} minutes doesn't fit in three digits", offset_minutes)));
+        }
+        // chrono keeps a leap second as second 59 with an extra second of nanoseconds
+        let leap = time.nanosecond() >= 1_000_000_000;
+        Ok(CimTimestamp {
+            year: Some(year),
+            month: Some(time.month() as u8),
+            day: Some(time.day() as u8),
+            hour: Some(time.hour() as u8),
+            minute: Some(time.minute() as u8),
+            second: Some(time.second() as u8 + leap as u8),
+            microsecond: Microseconds { value: time.nanosecond() / 1000 % 1_000_000, precision: 6 },
+            offset_minutes: offset_minutes as i16,
+        })
+    }
+}
+
+impl CimInterval {
+    // The length of the interval, with wildcarded fields counting as zero
+    pub fn to_duration(&self) -> Duration {
+        let seconds = self.days.unwrap_or(0) as u64 * 86_400
+            + self.hours.unwrap_or(0) as u64 * 3_600
+            + self.minutes.unwrap_or(0) as u64 * 60
+            + self.seconds.unwrap_or(0) as u64;
+        Duration::from_secs(seconds) + Duration::from_micros(self.microseconds.value as u64)
+    }
+}
+
+// Intervals longer than 99999999 days are capped, as the day field only has eight digits
+impl From<Duration> for CimInterval {
+    fn from(duration: Duration) -> Self {
+        let seconds = duration.as_secs();
+        CimInterval {
+            days: Some((seconds / 86_400).min(99_999_999) as u32),
+            hours: Some((seconds / 3_600 % 24) as u8),
+            minutes: Some((seconds / 60 % 60) as u8),
+            seconds: Some((seconds % 60) as u8),
+            microseconds: Microseconds { value: duration.subsec_micros(), precision: 6 },
+        }
+    }
+}
+
+impl Microseconds {
+    // "500000" is 500000µs at full precision, "5*****" is 500000µs known to a tenth of a second
+    fn parse(digits: &str) -> Option<Microseconds> {
+        let significant = digits.bytes().take_while(u8::is_ascii_digit).count();
+        if !digits[significant..].bytes().all(|b| b == b'*') {
+            return None;
+        }
+        let value = if significant == 0 { 0 } else { digits[..significant].parse::<u32>().ok()? };
+        Some(Microseconds { value: value * 10u32.pow(6 - significant as u32), precision: significant as u8 })
+    }
+}
+
+impl fmt::Display for Microseconds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = format!("{:06}", self.value);
+        let precision = (self.precision as usize).min(6);
+        write!(f, "{}{}", &digits[..precision], "*".repeat(6 - precision))
+    }
+}
+
+// Writes a field zero-padded to its width, or as asterisks when it's a wildcard
+fn write_field<T: fmt::Display>(f: &mut fmt::Formatter<'_>, field: Option<T>, width: usize) -> fmt::Result {
+    match field {
+        Some(value) => write!(f, "{:0width$}", value, width = width),
+        None => f.write_str(&"*".repeat(width)),
+    }
+}
+
+impl fmt::Display for CimTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_field(f, self.year, 4)?;
+        write_field(f, self.month, 2)?;
+        write_field(f, self.day, 2)?;
+        write_field(f, self.hour, 2)?;
+        write_field(f, self.minute, 2)?;
+        write_field(f, self.second, 2)?;
+        let sign = if self.offset_minutes < 0 { '-' } else { '+' };
+        write!(f, ".{}{}{:03}", self.microsecond, sign, self.offset_minutes.unsigned_abs())
+    }
+}
+
+impl fmt::Display for CimInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_field(f, self.days, 8)?;
+        write_field(f, self.hours, 2)?;
+        write_field(f, self.minutes, 2)?;
+        write_field(f, self.seconds, 2)?;
+        write!(f, ".{}:000", self.microseconds)
+    }
+}
+
+impl fmt::Display for CimDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CimDateTime::Timestamp(timestamp) => timestamp.fmt(f),
+            CimDateTime::Interval(interval) => interval.fmt(f),
+        }
+    }
+}
+
+impl FromStr for CimDateTime {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CimDateTime::parse(s)
+    }
+}
+
+impl FromStr for CimTimestamp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CimDateTime::parse(s)?
+            .as_timestamp()
+            .copied()
+            .ok_or_else(|| Error::parse("CIM_DATETIME", format!("'{}': expected a timestamp, not an interval", s)))
+    }
+}
+
+impl FromStr for CimInterval {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CimDateTime::parse(s)?
+            .as_interval()
+            .copied()
+            .ok_or_else(|| Error::parse("CIM_DATETIME", format!("'{}': expected an interval, not a timestamp", s)))
+    }
+}
+
+// All three serialise as their CIM string form
+macro_rules! string_serde {
+    ($($name:ident),*) => {$(
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+string_serde!(CimDateTime, CimTimestamp, CimInterval);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(value: &str) -> CimTimestamp {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn timestamps_and_intervals() {
+        let boot = timestamp("20240105083012.500000+060");
+        assert_eq!(boot.to_utc().unwrap().to_rfc3339(), "2024-01-05T07:30:12.500+00:00");
+        assert_eq!(boot.to_string(), "20240105083012.500000+060");
+
+        let partial = timestamp("2024****083012.5*****-300");
+        assert_eq!((partial.month, partial.microsecond), (None, Microseconds { value: 500_000, precision: 1 }));
+        assert_eq!(partial.to_datetime(), None);
+
+        let interval: CimInterval = "00000001020304.000000:000".parse().unwrap();
+        assert_eq!(interval.to_duration(), Duration::from_secs(86_400 + 2 * 3_600 + 3 * 60 + 4));
+        assert!("20240105083012.500000".parse::<CimDateTime>().is_err());
+        assert!("20241305083012.500000+060".parse::<CimDateTime>().is_err());
+    }
+
+    #[test]
+    fn leap_seconds() {
+        let leap = timestamp("20161231235960.250000+000");
+        let utc = leap.to_utc().unwrap();
+        assert_eq!(utc.second(), 59);
+        assert_eq!(utc.nanosecond(), 1_250_000_000);
+        assert_eq!(CimTimestamp::try_from(utc).unwrap(), leap);
+        assert!("20161231235961.000000+000".parse::<CimDateTime>().is_err());
+    }
+
+    #[test]
+    fn from_datetime() {
+        let time = DateTime::parse_from_rfc3339("2024-06-01T12:00:00.5+02:00").unwrap();
+        let cim = CimTimestamp::try_from(time).unwrap();
+        assert_eq!(cim.to_string(), "20240601120000.500000+120");
+        assert_eq!(cim.to_datetime(), Some(time));
+
+        let year = |year: i32| CimTimestamp::try_from(Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(year(0).unwrap().to_string(), "00000101000000.000000+000");
+        assert_eq!(year(9999).unwrap().to_string(), "99990101000000.000000+000");
+        assert!(matches!(year(-1), Err(Error::Parse { .. })));
+        assert!(matches!(year(10_000), Err(Error::Parse { .. })));
+
+        let far_east = FixedOffset::east_opt(17 * 3_600).unwrap().with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert!(CimTimestamp::try_from(far_east).is_err());
+    }
+}
@@ -0,0 +1,503 @@
+use serde::{Deserialize, Serialize};
+use crate::{ByteSize, CPUCacheSize, CPUInfo, CoreKind, Feature, FeatureSet};
+
+// What the CPUID instruction reports about the processor it runs on. Decoding goes through a
+// (leaf, subleaf) -> [eax, ebx, ecx, edx] function and an XGETBV function for the extended
+// control registers, so register dumps captured on other machines can be replayed on any
+// architecture.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cpuid {
+    pub vendor: String, // e.g. "GenuineIntel", "AuthenticAMD"
+    pub brand: Option<String>,
+    pub family: u32,    // Display family and model, with the extended fields folded in
+    pub model: u32,
+    pub stepping: u32,
+    pub caches: Vec<CpuidCache>,
+    pub topology: Option<CpuidTopology>,
+    pub hypervisor: Option<String>, // The hypervisor vendor when running as a guest, e.g. "KVMKVMKVM"
+    pub features: FeatureSet,
+    #[serde(default)]
+    pub core_kind: Option<CoreKind>, // The kind of core this was read on, on Intel hybrid processors
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    Data,
+    Instruction,
+    Unified,
+}
+
+// One cache from the deterministic cache parameters (leaf 4 on Intel, 0x8000001D on AMD)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuidCache {
+    pub level: u8,
+    pub kind: CacheKind,
+    pub size: ByteSize,
+    pub ways: u32,
+    pub line_size: u32,
+    pub sets: u32,
+    pub shared_by: u32, // The most logical processors that can share one instance
+}
+
+// From the extended topology leaves (0x1F, or 0xB on older processors)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuidTopology {
+    pub threads_per_core: u32,
+    pub logical_per_package: u32,
+}
+
+impl Cpuid {
+    // Reads the processor we're running on, None on anything but x86 and x86-64
+    pub fn read() -> Option<Cpuid> {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            Some(Cpuid::decode(native::cpuid, native::xgetbv))
+        }
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        {
+            None
+        }
+    }
+
+    // `xgetbv` is only called once CPUID reports OSXSAVE, as the instruction faults otherwise
+    pub fn decode<F, X>(cpuid: F, xgetbv: X) -> Cpuid
+    where
+        F: Fn(u32, u32) -> [u32; 4],
+        X: Fn(u32) -> u64,
+    {
+        let leaf0 = cpuid(0, 0);
+        let max_leaf = leaf0[0];
+        let max_extended_leaf = cpuid(0x8000_0000, 0)[0];
+        let vendor = registers_to_string(&[leaf0[1], leaf0[3], leaf0[2]]);
+
+        let leaf1 = if max_leaf >= 1 { cpuid(1, 0) } else { [0; 4] };
+        let signature = leaf1[0];
+        let base_family = (signature >> 8) & 0xF;
+        let base_model = (signature >> 4) & 0xF;
+        let family = if base_family == 0xF { base_family + ((signature >> 20) & 0xFF) } else { base_family };
+        let model = if base_family == 0x6 || base_family == 0xF {
+            (((signature >> 16) & 0xF) << 4) + base_model
+        } else {
+            base_model
+        };
+
+        let brand = (max_extended_leaf >= 0x8000_0004)
+            .then(|| {
+                let registers: Vec<u32> = (0x8000_0002..=0x8000_0004).flat_map(|leaf| cpuid(leaf, 0)).collect();
+                registers_to_string(&registers)
+            })
+            .filter(|brand| !brand.is_empty());
+
+        // AMD and Hygon describe their caches in 0x8000001D when topology extensions are present
+        let amd = vendor == "AuthenticAMD" || vendor == "HygonGenuine";
+        let topology_extensions = max_extended_leaf >= 0x8000_0001 && cpuid(0x8000_0001, 0)[2] & (1 << 22) != 0;
+        let caches = if amd && topology_extensions && max_extended_leaf >= 0x8000_001D {
+            caches(&cpuid, 0x8000_001D)
+        } else if !amd && max_leaf >= 4 {
+            caches(&cpuid, 4)
+        } else {
+            Vec::new()
+        };
+
+        let topology = if max_leaf >= 0x1F && cpuid(0x1F, 0)[1] != 0 {
+            topology(&cpuid, 0x1F)
+        } else if max_leaf >= 0xB {
+            topology(&cpuid, 0xB)
+        } else {
+            None
+        };
+
+        // Bit 31 of leaf 1 ECX is reserved for hypervisors to announce themselves, and they
+        // put their vendor signature in leaf 0x40000000
+        let hypervisor = (leaf1[2] & (1 << 31) != 0).then(|| {
+            let leaf = cpuid(0x4000_0000, 0);
+            registers_to_string(&[leaf[1], leaf[2], leaf[3]])
+        });
+
+        // Hybrid processors (leaf 7 EDX bit 15) report the type of the core running the
+        // instruction in bits 31:24 of leaf 0x1A EAX: 0x20 for Atom, 0x40 for Core
+        let hybrid = max_leaf >= 7 && cpuid(7, 0)[3] & (1 << 15) != 0;
+        let core_kind = if hybrid && max_leaf >= 0x1A {
+            match cpuid(0x1A, 0)[0] >> 24 {
+                0x20 => Some(CoreKind::Efficiency),
+                0x40 => Some(CoreKind::Performance),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        Cpuid {
+            features: features(&cpuid, &xgetbv, max_leaf, max_extended_leaf),
+            vendor,
+            brand,
+            family,
+            model,
+            stepping: signature & 0xF,
+            caches,
+            topology,
+            hypervisor,
+            core_kind,
+        }
+    }
+
+    // Per-package cache totals in the same shape as Win32_Processor and our sysfs reader: every
+    // instance of a level in the package summed, with data and instruction caches added up
+    pub fn cache_size(&self) -> CPUCacheSize {
+        let logical = self.topology.map(|topology| topology.logical_per_package).unwrap_or(1).max(1);
+        let total = |level: u8| {
+            let caches: Vec<&CpuidCache> = self.caches.iter().filter(|cache| cache.level == level).collect();
+            (!caches.is_empty()).then(|| {
+                caches
+                    .iter()
+                    .map(|cache| {
+                        let instances = logical.div_ceil(cache.shared_by.clamp(1, logical));
+                        ByteSize::from_bytes(cache.size.bytes() * instances as u64)
+                    })
+                    .sum()
+            })
+        };
+        CPUCacheSize { l1: total(1), l2: total(2), l3: total(3) }
+    }
+}
+
+impl CPUInfo {
+    // Fills in whatever the OS left blank. Values the OS did report are kept.
+    pub(crate) fn fill_from_cpuid(&mut self, cpuid: &Cpuid) {
+        if self.vendor.is_empty() {
+            self.vendor = cpuid.vendor.clone();
+        }
+        if self.name.is_empty() {
+            self.name = cpuid.brand.clone().unwrap_or_default();
+        }
+        let caches = cpuid.cache_size();
+        self.cache_size.l1 = self.cache_size.l1.or(caches.l1);
+        self.cache_size.l2 = self.cache_size.l2.or(caches.l2);
+        self.cache_size.l3 = self.cache_size.l3.or(caches.l3);
+        if self.cores == 0 {
+            if let Some(topology) = cpuid.topology {
+                self.cores = topology.logical_per_package / topology.threads_per_core.max(1);
+            }
+        }
+        // The OS knows better: it lists what it has enabled, and leaves out what it hasn't
+        // (e.g. AVX after "noxsave") or what's broken on this stepping
+        if self.features.is_empty() {
+            self.features = cpuid.features.clone();
+        }
+        if self.microarchitecture.is_none() {
+            self.microarchitecture = crate::microarch::x86(&cpuid.vendor, cpuid.family, cpuid.model, cpuid.stepping).map(String::from);
+        }
+    }
+}
+
+// Registers hold their text as little-endian bytes, padded with NULs
+fn registers_to_string(registers: &[u32]) -> String {
+    let bytes: Vec<u8> = registers.iter().flat_map(|register| register.to_le_bytes()).collect();
+    String::from_utf8_lossy(&bytes).trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string()
+}
+
+// Walks the subleaves of a deterministic cache parameters leaf until the null cache type
+fn caches<F: Fn(u32, u32) -> [u32; 4]>(cpuid: &F, leaf: u32) -> Vec<CpuidCache> {
+    let mut caches = Vec::new();
+    for subleaf in 0..32 {
+        let [eax, ebx, ecx, _] = cpuid(leaf, subleaf);
+        let kind = match eax & 0x1F {
+            1 => CacheKind::Data,
+            2 => CacheKind::Instruction,
+            3 => CacheKind::Unified,
+            _ => break,
+        };
+        let ways = (ebx >> 22) + 1;
+        let partitions = ((ebx >> 12) & 0x3FF) + 1;
+        let line_size = (ebx & 0xFFF) + 1;
+        let sets = ecx + 1;
+        caches.push(CpuidCache {
+            level: ((eax >> 5) & 0x7) as u8,
+            kind,
+            size: ByteSize::from_bytes(ways as u64 * partitions as u64 * line_size as u64 * sets as u64),
+            ways,
+            line_size,
+            sets,
+            shared_by: ((eax >> 14) & 0xFFF) + 1,
+        });
+    }
+    caches
+}
+
+// Each subleaf describes one level (SMT, core, module, tile, die) with the x2APIC ID shift to
+// the next level up and the logical processors it spans. The core level only spans the next
+// level, so on parts with module, tile or die levels the package holds 1 << (top shift - core
+// shift) of those.
+fn topology<F: Fn(u32, u32) -> [u32; 4]>(cpuid: &F, leaf: u32) -> Option<CpuidTopology> {
+    let mut threads_per_core = None;
+    let mut core = None;
+    let mut top = None;
+    for subleaf in 0..8 {
+        let [eax, ebx, ecx, _] = cpuid(leaf, subleaf);
+        let level_type = (ecx >> 8) & 0xFF;
+        if level_type == 0 {
+            break;
+        }
+        let level = (eax & 0x1F, ebx & 0xFFFF);
+        match level_type {
+            1 => threads_per_core = Some(level.1),
+            2 => core = Some(level),
+            _ => {}
+        }
+        top = Some(level);
+    }
+    let (top_shift, top_logical) = top?;
+    let logical_per_package = match core {
+        Some((shift, logical)) => logical.checked_shl(top_shift.saturating_sub(shift))?,
+        None => top_logical,
+    };
+    if logical_per_package == 0 {
+        return None;
+    }
+    Some(CpuidTopology {
+        threads_per_core: threads_per_core.unwrap_or(1).max(1),
+        logical_per_package,
+    })
+}
+
+// (leaf, subleaf, register index into [eax, ebx, ecx, edx], bit, feature)
+const FEATURE_BITS: &[(u32, u32, usize, u32, Feature)] = &[
+    (1, 0, 3, 0, Feature::Fpu),
+    (1, 0, 3, 8, Feature::Cx8),
+    (1, 0, 3, 15, Feature::Cmov),
+    (1, 0, 3, 23, Feature::Mmx),
+    (1, 0, 3, 24, Feature::Fxsr),
+    (1, 0, 3, 25, Feature::Sse),
+    (1, 0, 3, 26, Feature::Sse2),
+    (1, 0, 2, 0, Feature::Sse3),
+    (1, 0, 2, 1, Feature::Pclmulqdq),
+    (1, 0, 2, 5, Feature::Vmx),
+    (1, 0, 2, 9, Feature::Ssse3),
+    (1, 0, 2, 12, Feature::Fma),
+    (1, 0, 2, 13, Feature::Cx16),
+    (1, 0, 2, 19, Feature::Sse4_1),
+    (1, 0, 2, 20, Feature::Sse4_2),
+    (1, 0, 2, 22, Feature::Movbe),
+    (1, 0, 2, 23, Feature::Popcnt),
+    (1, 0, 2, 25, Feature::Aes),
+    (1, 0, 2, 26, Feature::Xsave),
+    (1, 0, 2, 27, Feature::Osxsave),
+    (1, 0, 2, 28, Feature::Avx),
+    (1, 0, 2, 29, Feature::F16c),
+    (1, 0, 2, 30, Feature::Rdrand),
+    (1, 0, 2, 31, Feature::Hypervisor),
+    (7, 0, 1, 3, Feature::Bmi1),
+    (7, 0, 1, 5, Feature::Avx2),
+    (7, 0, 1, 8, Feature::Bmi2),
+    (7, 0, 1, 16, Feature::Avx512F),
+    (7, 0, 1, 17, Feature::Avx512Dq),
+    (7, 0, 1, 18, Feature::Rdseed),
+    (7, 0, 1, 19, Feature::Adx),
+    (7, 0, 1, 21, Feature::Avx512Ifma),
+    (7, 0, 1, 28, Feature::Avx512Cd),
+    (7, 0, 1, 29, Feature::ShaNi),
+    (7, 0, 1, 30, Feature::Avx512Bw),
+    (7, 0, 1, 31, Feature::Avx512Vl),
+    (7, 0, 2, 1, Feature::Avx512Vbmi),
+    (7, 0, 2, 8, Feature::Gfni),
+    (7, 0, 2, 9, Feature::Vaes),
+    (7, 0, 2, 10, Feature::Vpclmulqdq),
+    (7, 0, 2, 11, Feature::Avx512Vnni),
+    (7, 1, 0, 4, Feature::AvxVnni),
+    (7, 1, 0, 5, Feature::Avx512Bf16),
+    (0x8000_0001, 0, 2, 0, Feature::LahfLm),
+    (0x8000_0001, 0, 2, 2, Feature::Svm),
+    (0x8000_0001, 0, 2, 5, Feature::Lzcnt),
+    (0x8000_0001, 0, 2, 6, Feature::Sse4a),
+    (0x8000_0001, 0, 3, 11, Feature::Syscall),
+    (0x8000_0001, 0, 3, 20, Feature::Nx),
+    (0x8000_0001, 0, 3, 29, Feature::Lm),
+];
+
+// Need the AVX state (XCR0 bits 1 and 2) enabled by the OS
+const AVX_FEATURES: &[Feature] = &[
+    Feature::Avx, Feature::Avx2, Feature::Fma, Feature::F16c, Feature::Vaes, Feature::Vpclmulqdq, Feature::AvxVnni,
+];
+
+// Also need the opmask and ZMM state (XCR0 bits 5 to 7)
+const AVX512_FEATURES: &[Feature] = &[
+    Feature::Avx512F, Feature::Avx512Dq, Feature::Avx512Ifma, Feature::Avx512Cd, Feature::Avx512Bw,
+    Feature::Avx512Vl, Feature::Avx512Vbmi, Feature::Avx512Vnni, Feature::Avx512Bf16,
+];
+
+fn features<F, X>(cpuid: &F, xgetbv: &X, max_leaf: u32, max_extended_leaf: u32) -> FeatureSet
+where
+    F: Fn(u32, u32) -> [u32; 4],
+    X: Fn(u32) -> u64,
+{
+    let max_subleaf_7 = if max_leaf >= 7 { cpuid(7, 0)[0] } else { 0 };
+    let supported = |leaf: u32, subleaf: u32| {
+        if leaf >= 0x8000_0000 {
+            leaf <= max_extended_leaf
+        } else {
+            leaf <= max_leaf && (leaf != 7 || subleaf <= max_subleaf_7)
+        }
+    };
+
+    let mut leaves: Vec<((u32, u32), [u32; 4])> = Vec::new();
+    let mut features = FeatureSet::new();
+    for &(leaf, subleaf, register, bit, feature) in FEATURE_BITS {
+        if !supported(leaf, subleaf) {
+            continue;
+        }
+        let registers = match leaves.iter().find(|(key, _)| *key == (leaf, subleaf)) {
+            Some((_, registers)) => *registers,
+            None => {
+                let registers = cpuid(leaf, subleaf);
+                leaves.push(((leaf, subleaf), registers));
+                registers
+            }
+        };
+        if registers[register] & (1 << bit) != 0 {
+            features.insert(feature);
+        }
+    }
+
+    // The processor supporting AVX isn't enough: the OS must also save the wider registers on
+    // context switches, which it advertises in XCR0
+    let xcr0 = if features.contains(Feature::Osxsave) { xgetbv(0) } else { 0 };
+    let disabled: &[&[Feature]] = match xcr0 {
+        xcr0 if xcr0 & 0x6 != 0x6 => &[AVX_FEATURES, AVX512_FEATURES],
+        xcr0 if xcr0 & 0xE6 != 0xE6 => &[AVX512_FEATURES],
+        _ => &[],
+    };
+    for &feature in disabled.iter().copied().flatten() {
+        features.remove(feature);
+    }
+    features
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod native {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::{__cpuid_count, _xgetbv};
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::{__cpuid_count, _xgetbv};
+
+    // __cpuid_count stopped being unsafe in newer toolchains
+    #[allow(unused_unsafe)]
+    pub(super) fn cpuid(leaf: u32, subleaf: u32) -> [u32; 4] {
+        let result = unsafe { __cpuid_count(leaf, subleaf) };
+        [result.eax, result.ebx, result.ecx, result.edx]
+    }
+
+    // Only valid once CPUID reports OSXSAVE, which Cpuid::decode checks before calling it
+    pub(super) fn xgetbv(register: u32) -> u64 {
+        #[target_feature(enable = "xsave")]
+        #[allow(unused_unsafe)]
+        unsafe fn read(register: u32) -> u64 {
+            unsafe { _xgetbv(register) }
+        }
+        unsafe { read(register) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::X86_64Level;
+
+    // A Skylake-SP: AVX, AVX2 and AVX-512 in CPUID, with OSXSAVE unless `osxsave` is false
+    fn skylake_sp(osxsave: bool) -> impl Fn(u32, u32) -> [u32; 4] {
+        move |leaf, subleaf| match (leaf, subleaf) {
+            (0, _) => [0x16, 0x756E_6547, 0x6C65_746E, 0x4965_6E69],
+            (1, _) => {
+                let ecx = 0x77FE_FBFF | if osxsave { 1 << 27 } else { 0 };
+                [0x0005_0654, 0x0010_0800, ecx, 0xBFEB_FBFF]
+            }
+            (7, 0) => [0, 0xD39F_FFFB, 0x0000_0808, 0xBC00_0400],
+            (0x8000_0000, _) => [0x8000_0008, 0, 0, 0],
+            (0x8000_0001, _) => [0, 0, 0x0000_0121, 0x2C10_0800],
+            _ => [0; 4],
+        }
+    }
+
+    #[test]
+    fn avx_needs_the_os_to_enable_its_state() {
+        let full = Cpuid::decode(skylake_sp(true), |_| 0xE7).features;
+        assert!(full.contains_all(&[Feature::Avx, Feature::Avx2, Feature::Fma, Feature::Avx512F, Feature::Avx512Vl]));
+        assert_eq!(X86_64Level::detect(&full), Some(X86_64Level::V4));
+
+        // AVX state but no opmask or ZMM state, e.g. a hypervisor hiding AVX-512
+        let avx = Cpuid::decode(skylake_sp(true), |_| 0x7).features;
+        assert!(avx.contains_all(&[Feature::Avx, Feature::Avx2, Feature::Fma]));
+        assert!(!avx.contains(Feature::Avx512F));
+        assert_eq!(X86_64Level::detect(&avx), Some(X86_64Level::V3));
+
+        let sse = Cpuid::decode(skylake_sp(true), |_| 0x3).features;
+        assert!(!sse.contains(Feature::Avx) && !sse.contains(Feature::F16c));
+        assert!(sse.contains_all(&[Feature::Sse4_2, Feature::Bmi2]));
+        assert_eq!(X86_64Level::detect(&sse), Some(X86_64Level::V2));
+
+        // Without OSXSAVE, XGETBV would fault, so it mustn't be called at all
+        let disabled = Cpuid::decode(skylake_sp(false), |_| panic!("XGETBV without OSXSAVE")).features;
+        assert!(!disabled.contains(Feature::Avx));
+        assert!(disabled.contains(Feature::Xsave));
+    }
+
+    // A two-die Cascade Lake-AP package in leaf 0x1F: 2 threads per core, 24 cores per die.
+    // The die level's own count only covers one die.
+    #[test]
+    fn topology_counts_every_die_in_the_package() {
+        let cascade_lake_ap = |leaf, subleaf| match (leaf, subleaf) {
+            (0, _) => [0x1F, 0x756E_6547, 0x6C65_746E, 0x4965_6E69],
+            (0x1F, 0) => [1, 2, 0x100, 0],
+            (0x1F, 1) => [6, 48, 0x201, 0],
+            (0x1F, 2) => [7, 48, 0x502, 0],
+            (0x1F, subleaf) => [0, 0, subleaf, 0],
+            _ => [0; 4],
+        };
+        let topology = Cpuid::decode(cascade_lake_ap, |_| 0).topology.unwrap();
+        assert_eq!(topology.threads_per_core, 2);
+        assert_eq!(topology.logical_per_package, 96);
+
+        // Without the die level the core level spans the package
+        let single_die = |leaf, subleaf| match (leaf, subleaf) {
+            (0x1F, 2) => [0, 0, 2, 0],
+            _ => cascade_lake_ap(leaf, subleaf),
+        };
+        assert_eq!(Cpuid::decode(single_die, |_| 0).topology.unwrap().logical_per_package, 48);
+    }
+
+    fn blank_cpu(features: FeatureSet) -> CPUInfo {
+        CPUInfo {
+            vendor: String::new(),
+            model: String::new(),
+            name: String::new(),
+            frequency: Default::default(),
+            architecture: crate::CPUArchitecture::X64,
+            cores: 0,
+            logical_cores: 0,
+            cache_size: Default::default(),
+            virtualisation: false,
+            features,
+            x86_64_level: None,
+            microarchitecture: None,
+            performance_cores: None,
+            efficiency_cores: None,
+            riscv_isa: None,
+            mmu: None,
+        }
+    }
+
+    #[test]
+    fn fill_keeps_the_os_features() {
+        let cpuid = Cpuid::decode(skylake_sp(true), |_| 0xE7);
+        let os = FeatureSet::from_cpuinfo_flags("fpu sse sse2 pni avx");
+        let mut cpu = blank_cpu(os.clone());
+        cpu.fill_from_cpuid(&cpuid);
+        assert_eq!(cpu.features, os);
+        assert_eq!(cpu.vendor, "GenuineIntel");
+        assert_eq!(cpu.microarchitecture.as_deref(), Some("Skylake"));
+
+        let mut cpu = blank_cpu(FeatureSet::new());
+        cpu.fill_from_cpuid(&cpuid);
+        assert_eq!(cpu.features, cpuid.features);
+    }
+}
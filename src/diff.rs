@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::snapshot::Component;
+use crate::{CPUInfo, GPUInfo, MemInfo, OSInfo, SystemSnapshot};
+
+// The hardware and OS changes between two snapshots of the same machine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub component: Component,
+    pub key: String, // The identity both sides were matched on, e.g. a serial number or slot
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added { after: Value },
+    Removed { before: Value },
+    Modified { fields: Vec<FieldChange> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String, // Dotted path into the component, e.g. "cache_size.l2"
+    pub before: Value,
+    pub after: Value,
+}
+
+// A key that identifies the same physical (or logical) component across two collections
+pub trait Identity {
+    fn identity(&self) -> String;
+
+    // Fields that change between collections without the component itself changing
+    fn volatile_fields() -> &'static [&'static str] {
+        &[]
+    }
+}
+
+impl Identity for CPUInfo {
+    // Processors don't expose a serial, so they're matched by socket order in `diff_items`
+    fn identity(&self) -> String {
+        String::from("CPU")
+    }
+
+    fn volatile_fields() -> &'static [&'static str] {
+        &["frequency"]
+    }
+}
+
+impl Identity for GPUInfo {
+    // The PCI slot on Linux. Windows' DeviceID only numbers the adapters in enumeration order,
+    // so the PnP device instance path, which names the hardware and where it sits, is used instead.
+    fn identity(&self) -> String {
+        self.pnp_device_id.clone().unwrap_or_else(|| self.device_id.clone())
+    }
+
+    fn volatile_fields() -> &'static [&'static str] {
+        &["index", "device_id"]
+    }
+}
+
+impl Identity for MemInfo {
+    fn identity(&self) -> String {
+        let serial = self.serial_number.trim();
+        let placeholder = serial.is_empty() || serial.chars().all(|c| c == '0') || serial.eq_ignore_ascii_case("unknown");
+        if placeholder { self.name.clone() } else { serial.to_string() }
+    }
+
+    fn volatile_fields() -> &'static [&'static str] {
+        &["index", "free_memory"]
+    }
+}
+
+impl Identity for OSInfo {
+    fn identity(&self) -> String {
+        String::from("OS")
+    }
+
+    fn volatile_fields() -> &'static [&'static str] {
+        &["boot_time"]
+    }
+}
+
+impl SnapshotDiff {
+    pub fn between(before: &SystemSnapshot, after: &SystemSnapshot) -> SnapshotDiff {
+        let mut changes = Vec::new();
+        changes.extend(diff_items(Component::Cpu, &before.cpus, &after.cpus));
+        changes.extend(diff_items(Component::Gpu, &before.gpus, &after.gpus));
+        changes.extend(diff_items(Component::Os, before.os.as_slice(), after.os.as_slice()));
+        changes.extend(diff_items(Component::Memory, &before.memory, &after.memory));
+        SnapshotDiff { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+// Matches the items of both sides by identity and reports what was added, removed or modified.
+// Items sharing an identity are told apart by the order they appear in, e.g. "CPU#1".
+pub fn diff_items<T: Identity + Serialize>(component: Component, before: &[T], after: &[T]) -> Vec<Change> {
+    let before = keyed(before);
+    let mut after = keyed(after);
+    let mut changes = Vec::new();
+
+    for (key, old) in before {
+        match after.iter().position(|(other, _)| *other == key) {
+            Some(position) => {
+                let (_, new) = after.remove(position);
+                let fields = diff_fields(&old, &new, T::volatile_fields());
+                if !fields.is_empty() {
+                    changes.push(Change { component, key, kind: ChangeKind::Modified { fields } });
+                }
+            }
+            None => changes.push(Change { component, key, kind: ChangeKind::Removed { before: old } }),
+        }
+    }
+    for (key, new) in after {
+        changes.push(Change { component, key, kind: ChangeKind::Added { after: new } });
+    }
+
+    changes
+}
+
+fn keyed<T: Identity + Serialize>(items: &[T]) -> Vec<(String, Value)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    items
+        .iter()
+        .map(|item| {
+            let identity = item.identity();
+            let count = seen.entry(identity.clone()).or_default();
+            let key = if *count == 0 { identity } else { format!("{}#{}", identity, count) };
+            *count += 1;
+            (key, serde_json::to_value(item).unwrap_or(Value::Null))
+        })
+        .collect()
+}
+
+fn diff_fields(before: &Value, after: &Value, volatile: &[&str]) -> Vec<FieldChange> {
+    let mut before_fields = Vec::new();
+    let mut after_fields = Vec::new();
+    flatten("", before, &mut before_fields);
+    flatten("", after, &mut after_fields);
+
+    let mut changes = Vec::new();
+    for (field, old) in &before_fields {
+        let new = after_fields.iter().find(|(other, _)| other == field).map(|(_, value)| value.clone());
+        let new = new.unwrap_or(Value::Null);
+        if *old != new {
+            changes.push(FieldChange { field: field.clone(), before: old.clone(), after: new });
+        }
+    }
+    for (field, new) in after_fields {
+        if !before_fields.iter().any(|(other, _)| *other == field) {
+            changes.push(FieldChange { field, before: Value::Null, after: new });
+        }
+    }
+
+    changes.retain(|change| !volatile.contains(&change.field.as_str()));
+    changes
+}
+
+// Turns nested objects into (dotted path, leaf value) pairs. Arrays are compared as a whole.
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&path, value, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            match &change.kind {
+                ChangeKind::Added { .. } => writeln!(f, "+ {} {}", change.component, change.key)?,
+                ChangeKind::Removed { .. } => writeln!(f, "- {} {}", change.component, change.key)?,
+                ChangeKind::Modified { fields } => {
+                    writeln!(f, "~ {} {}", change.component, change.key)?;
+                    for field in fields {
+                        writeln!(f, "    {}: {} -> {}", field.field, field.before, field.after)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::*;
+    use crate::linux::SysRoot;
+    use crate::units::Frequency;
+    use crate::windows::FixtureSource;
+
+    fn windows() -> FixtureSource {
+        FixtureSource::from_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/windows/desktop.json")).unwrap()
+    }
+
+    fn dimm(name: &str, serial: &str) -> MemInfo {
+        MemInfo {
+            index: 0,
+            vendor: String::from("Kingston"),
+            model: String::new(),
+            name: name.to_string(),
+            serial_number: serial.to_string(),
+            part_number: String::from("KF432C16BB/8"),
+            speed_mts: Some(3200),
+            total_memory: 8 << 30,
+            free_memory: 0,
+        }
+    }
+
+    #[test]
+    fn added_removed_and_modified() {
+        let before = MemInfo::fetch_from_source(&windows()).unwrap();
+        let mut after = vec![before[0].clone(), dimm("DIMM2", "9C0D1E2F")];
+        after[0].part_number = String::from("KF432C16BB/8-RGB");
+
+        let changes = diff_items(Component::Memory, &before, &after);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0], Change {
+            component: Component::Memory,
+            key: String::from("1A2B3C4D"),
+            kind: ChangeKind::Modified {
+                fields: vec![FieldChange {
+                    field: String::from("part_number"),
+                    before: Value::from("KF432C16BB/8"),
+                    after: Value::from("KF432C16BB/8-RGB"),
+                }],
+            },
+        });
+        assert_eq!(changes[1].key, "5E6F7A8B");
+        assert!(matches!(&changes[1].kind, ChangeKind::Removed { before } if before["name"] == "DIMM2"));
+        assert_eq!(changes[2].key, "9C0D1E2F");
+        assert!(matches!(&changes[2].kind, ChangeKind::Added { after } if after["name"] == "DIMM2"));
+        assert!(diff_items(Component::Memory, &before, &before).is_empty());
+    }
+
+    #[test]
+    fn duplicate_identities_are_numbered() {
+        let cpu = CPUInfo::fetch_from_source(&windows()).unwrap().remove(0);
+        let keys: Vec<String> = keyed(&[cpu.clone(), cpu.clone(), cpu.clone()]).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["CPU", "CPU#1", "CPU#2"]);
+
+        let changes = diff_items(Component::Cpu, &[cpu.clone(), cpu.clone()], &[cpu]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].key, "CPU#1");
+        assert!(matches!(changes[0].kind, ChangeKind::Removed { .. }));
+    }
+
+    #[test]
+    fn volatile_fields_are_ignored() {
+        let before = MemInfo::fetch_from_source(&windows()).unwrap();
+        let mut after = before.clone();
+        after.reverse();
+        after.iter_mut().enumerate().for_each(|(index, module)| {
+            module.index = index as u8;
+            module.free_memory /= 2;
+        });
+        assert!(diff_items(Component::Memory, &before, &after).is_empty());
+
+        let mut cpu = CPUInfo::fetch_from_source(&windows()).unwrap();
+        let before_cpu = cpu.clone();
+        cpu[0].frequency = Frequency::from_mhz(4200);
+        assert!(diff_items(Component::Cpu, &before_cpu, &cpu).is_empty());
+
+        // Reordered adapters keep their PnP identity even though Windows renumbers them
+        let gpus = GPUInfo::fetch_from_source(&windows()).unwrap();
+        let mut swapped = gpus.clone();
+        swapped.reverse();
+        for (index, gpu) in swapped.iter_mut().enumerate() {
+            gpu.index = index as u8;
+            gpu.device_id = format!("VideoController{}", index + 1);
+        }
+        assert!(diff_items(Component::Gpu, &gpus, &swapped).is_empty());
+
+        let mut replaced = gpus.clone();
+        replaced[0].pnp_device_id = Some(String::from("PCI\\VEN_10DE&DEV_2684&SUBSYS_16F310DE&REV_A1\\4&1B2C3D4E&0&0008"));
+        let changes = diff_items(Component::Gpu, &gpus, &replaced);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(changes[0].kind, ChangeKind::Removed { .. }));
+        assert!(matches!(changes[1].kind, ChangeKind::Added { .. }));
+    }
+
+    #[test]
+    fn placeholder_serials_fall_back_to_the_device_locator() {
+        for serial in ["", "  ", "00000000", "Unknown", "UNKNOWN"] {
+            assert_eq!(dimm("DIMM_A1", serial).identity(), "DIMM_A1", "{:?}", serial);
+        }
+        assert_eq!(dimm("DIMM_A1", " 1A2B3C4D ").identity(), "1A2B3C4D");
+
+        // Moving a module without a serial to another slot is a removal and an addition
+        let changes = diff_items(Component::Memory, &[dimm("DIMM_A1", "0000")], &[dimm("DIMM_B1", "0000")]);
+        let keys: Vec<&str> = changes.iter().map(|change| change.key.as_str()).collect();
+        assert_eq!(keys, ["DIMM_A1", "DIMM_B1"]);
+    }
+
+    #[test]
+    fn between_snapshots() {
+        let root = SysRoot::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/linux/desktop"));
+        let snapshot = SystemSnapshot {
+            timestamp: chrono::Utc::now(),
+            host: String::from("workstation"),
+            cpus: CPUInfo::fetch_from_root(&root).unwrap(),
+            gpus: GPUInfo::fetch_from_root(&root).unwrap(),
+            os: OSInfo::fetch_from_root(&root).unwrap().pop(),
+            memory: vec![dimm("DIMM_A1", "1A2B3C4D")],
+            memory_usage: None,
+            errors: Vec::new(),
+        };
+        let mut after = snapshot.clone();
+        after.gpus.clear();
+        after.memory.push(dimm("DIMM_B1", "5E6F7A8B"));
+        after.os.as_mut().unwrap().version = String::from("24.10");
+
+        let diff = SnapshotDiff::between(&snapshot, &after);
+        assert_eq!(diff.to_string(), "\
+- gpu 0000:03:00.0
+~ os OS
+    version: \"24.04\" -> \"24.10\"
++ memory 5E6F7A8B
+");
+        assert!(SnapshotDiff::between(&snapshot, &snapshot).is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+use std::io;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Error {
+    SourceUnavailable { source: String, reason: String }, // The data source could not be opened or queried
+    PermissionDenied { source: String },                  // The data source exists but requires more privileges
+    Parse { field: String, reason: String },              // The data source returned something we couldn't understand
+    UnsupportedPlatform { platform: String },             // There is no backend for this component on the current OS
+    Timeout { source: String },                           // The data source did not answer in time
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub(crate) fn io(source: impl Into<String>, err: io::Error) -> Self {
+        let source = source.into();
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied { source },
+            io::ErrorKind::TimedOut => Error::Timeout { source },
+            _ => Error::SourceUnavailable { source, reason: err.to_string() },
+        }
+    }
+
+    pub(crate) fn parse(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Error::Parse { field: field.into(), reason: reason.into() }
+    }
+
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub(crate) fn unsupported() -> Self {
+        Error::UnsupportedPlatform { platform: std::env::consts::OS.to_string() }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SourceUnavailable { source, reason } => write!(f, "{} is unavailable: {}", source, reason),
+            Error::PermissionDenied { source } => write!(f, "Permission denied while reading {}", source),
+            Error::Parse { field, reason } => write!(f, "Failed to parse {}: {}", field, reason),
+            Error::UnsupportedPlatform { platform } => write!(f, "Not supported on {}", platform),
+            Error::Timeout { source } => write!(f, "Timed out while reading {}", source),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,361 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::cpuid::Cpuid;
+use crate::{CPUArchitecture, Error};
+
+// Declares the Feature enum along with its canonical name, which is the flag Linux prints in
+// /proc/cpuinfo, and any other spellings that mean the same thing
+macro_rules! features {
+    ($($variant:ident => $name:literal $(| $alias:literal)*,)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum Feature {
+            $($variant,)*
+        }
+
+        impl Feature {
+            pub const ALL: &'static [Feature] = &[$(Feature::$variant,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Feature::$variant => $name,)*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Feature> {
+                match name.to_ascii_lowercase().as_str() {
+                    $($name $(| $alias)* => Some(Feature::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+features! {
+    // x86 / x86-64
+    Fpu => "fpu",
+    Cx8 => "cx8",
+    Cmov => "cmov",
+    Mmx => "mmx",
+    Fxsr => "fxsr",
+    Sse => "sse",
+    Sse2 => "sse2",
+    Syscall => "syscall",
+    Nx => "nx",
+    Lm => "lm",
+    Cx16 => "cx16",
+    LahfLm => "lahf_lm",
+    Popcnt => "popcnt",
+    Sse3 => "sse3" | "pni",
+    Ssse3 => "ssse3",
+    Sse4_1 => "sse4_1",
+    Sse4_2 => "sse4_2",
+    Sse4a => "sse4a",
+    Pclmulqdq => "pclmulqdq",
+    Avx => "avx",
+    Avx2 => "avx2",
+    F16c => "f16c",
+    Fma => "fma",
+    Bmi1 => "bmi1",
+    Bmi2 => "bmi2",
+    Lzcnt => "lzcnt" | "abm",
+    Movbe => "movbe",
+    Xsave => "xsave",
+    Osxsave => "osxsave",
+    Rdrand => "rdrand",
+    Rdseed => "rdseed",
+    Adx => "adx",
+    ShaNi => "sha_ni",
+    Vaes => "vaes",
+    Vpclmulqdq => "vpclmulqdq",
+    Gfni => "gfni",
+    Avx512F => "avx512f",
+    Avx512Bw => "avx512bw",
+    Avx512Cd => "avx512cd",
+    Avx512Dq => "avx512dq",
+    Avx512Vl => "avx512vl",
+    Avx512Ifma => "avx512ifma",
+    Avx512Vbmi => "avx512vbmi",
+    Avx512Vnni => "avx512_vnni",
+    Avx512Bf16 => "avx512_bf16",
+    AvxVnni => "avx_vnni",
+    Vmx => "vmx",
+    Svm => "svm",
+    Hypervisor => "hypervisor",
+
+    // Shared by x86 (AES-NI) and ARM (the AES instructions of the crypto extension)
+    Aes => "aes",
+
+    // ARM / AArch64
+    Fp => "fp",
+    Neon => "asimd" | "neon",
+    Pmull => "pmull",
+    Sha1 => "sha1",
+    Sha2 => "sha2",
+    Sha3 => "sha3",
+    Sha512 => "sha512",
+    Crc32 => "crc32",
+    Atomics => "atomics",
+    Fphp => "fphp",
+    Asimdhp => "asimdhp",
+    DotProd => "asimddp",
+    Sve => "sve",
+    Sve2 => "sve2",
+    I8mm => "i8mm",
+    Bf16 => "bf16",
+    Sm3 => "sm3",
+    Sm4 => "sm4",
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Feature::from_name(s).ok_or_else(|| Error::parse("feature", format!("unknown feature '{}'", s)))
+    }
+}
+
+impl Serialize for Feature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Feature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// The instruction set extensions a processor supports. Serialised as a sorted list of names;
+// names this version doesn't know are skipped when reading, so newer snapshots still load.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FeatureSet {
+    features: BTreeSet<Feature>,
+}
+
+impl FeatureSet {
+    pub fn new() -> Self {
+        FeatureSet::default()
+    }
+
+    pub fn contains(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    pub fn contains_all(&self, features: &[Feature]) -> bool {
+        features.iter().all(|&feature| self.contains(feature))
+    }
+
+    pub fn insert(&mut self, feature: Feature) -> bool {
+        self.features.insert(feature)
+    }
+
+    pub fn remove(&mut self, feature: Feature) -> bool {
+        self.features.remove(&feature)
+    }
+
+    pub fn extend_from(&mut self, other: &FeatureSet) {
+        self.features.extend(other.iter());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Feature> + '_ {
+        self.features.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    // Reads the space-separated `flags` (x86) or `Features` (ARM) line of /proc/cpuinfo,
+    // ignoring flags we don't model
+    pub fn from_cpuinfo_flags(flags: &str) -> FeatureSet {
+        flags.split_whitespace().filter_map(Feature::from_name).collect()
+    }
+
+    // Decodes the AT_HWCAP and AT_HWCAP2 auxiliary vector entries the kernel passes to every
+    // process. Only AArch64 is decoded, as x86 reports its features through CPUID instead.
+    // The bits mean something different on every architecture, so the caller names the one
+    // the vector came from rather than us assuming it's the host's.
+    pub fn from_hwcap(architecture: CPUArchitecture, hwcap: u64, hwcap2: u64) -> FeatureSet {
+        const HWCAP: &[(u32, Feature)] = &[
+            (0, Feature::Fp),
+            (1, Feature::Neon),
+            (3, Feature::Aes),
+            (4, Feature::Pmull),
+            (5, Feature::Sha1),
+            (6, Feature::Sha2),
+            (7, Feature::Crc32),
+            (8, Feature::Atomics),
+            (9, Feature::Fphp),
+            (10, Feature::Asimdhp),
+            (17, Feature::Sha3),
+            (18, Feature::Sm3),
+            (19, Feature::Sm4),
+            (20, Feature::DotProd),
+            (21, Feature::Sha512),
+            (22, Feature::Sve),
+        ];
+        const HWCAP2: &[(u32, Feature)] = &[(1, Feature::Sve2), (13, Feature::I8mm), (14, Feature::Bf16)];
+
+        if architecture != CPUArchitecture::Arm64 {
+            return FeatureSet::new();
+        }
+        fn bits(word: u64, table: &'static [(u32, Feature)]) -> impl Iterator<Item = Feature> {
+            table.iter().filter(move |(bit, _)| word & (1 << bit) != 0).map(|&(_, feature)| feature)
+        }
+        bits(hwcap, HWCAP).chain(bits(hwcap2, HWCAP2)).collect()
+    }
+
+    // Reads the features of the processor we're running on straight from CPUID. Empty on
+    // other architectures.
+    pub fn from_cpuid() -> FeatureSet {
+        Cpuid::read().map(|cpuid| cpuid.features).unwrap_or_default()
+    }
+}
+
+// The x86-64 psABI micro-architecture levels. Each level requires every feature of the levels
+// below it, and binaries built with e.g. -march=x86-64-v3 need at least that level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum X86_64Level {
+    #[serde(rename = "x86-64")]
+    V1,
+    #[serde(rename = "x86-64-v2")]
+    V2,
+    #[serde(rename = "x86-64-v3")]
+    V3,
+    #[serde(rename = "x86-64-v4")]
+    V4,
+}
+
+impl X86_64Level {
+    pub const ALL: [X86_64Level; 4] = [X86_64Level::V1, X86_64Level::V2, X86_64Level::V3, X86_64Level::V4];
+
+    // What each level adds on top of the previous one
+    pub fn required_features(self) -> &'static [Feature] {
+        match self {
+            X86_64Level::V1 => &[
+                Feature::Cmov, Feature::Cx8, Feature::Fpu, Feature::Fxsr, Feature::Mmx,
+                Feature::Syscall, Feature::Sse, Feature::Sse2,
+            ],
+            X86_64Level::V2 => &[
+                Feature::Cx16, Feature::LahfLm, Feature::Popcnt, Feature::Sse3,
+                Feature::Sse4_1, Feature::Sse4_2, Feature::Ssse3,
+            ],
+            X86_64Level::V3 => &[
+                Feature::Avx, Feature::Avx2, Feature::Bmi1, Feature::Bmi2, Feature::F16c,
+                Feature::Fma, Feature::Lzcnt, Feature::Movbe,
+            ],
+            X86_64Level::V4 => &[
+                Feature::Avx512F, Feature::Avx512Bw, Feature::Avx512Cd, Feature::Avx512Dq, Feature::Avx512Vl,
+            ],
+        }
+    }
+
+    // The highest level whose features (and those of every level below it) are all present.
+    // The set must only hold what the OS has enabled, as AVX and AVX-512 are unusable without
+    // its support: /proc/cpuinfo flags, or CPUID features gated on XCR0.
+    pub fn detect(features: &FeatureSet) -> Option<X86_64Level> {
+        X86_64Level::ALL
+            .into_iter()
+            .take_while(|level| features.contains_all(level.required_features()))
+            .last()
+    }
+
+    // The -march name, e.g. "x86-64-v3"
+    pub fn name(self) -> &'static str {
+        match self {
+            X86_64Level::V1 => "x86-64",
+            X86_64Level::V2 => "x86-64-v2",
+            X86_64Level::V3 => "x86-64-v3",
+            X86_64Level::V4 => "x86-64-v4",
+        }
+    }
+}
+
+impl fmt::Display for X86_64Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromIterator<Feature> for FeatureSet {
+    fn from_iter<I: IntoIterator<Item = Feature>>(iter: I) -> Self {
+        FeatureSet { features: iter.into_iter().collect() }
+    }
+}
+
+impl Extend<Feature> for FeatureSet {
+    fn extend<I: IntoIterator<Item = Feature>>(&mut self, iter: I) {
+        self.features.extend(iter);
+    }
+}
+
+impl fmt::Display for FeatureSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.iter().map(Feature::name).collect();
+        f.write_str(&names.join(" "))
+    }
+}
+
+impl Serialize for FeatureSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(Feature::name))
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names: Vec<String> = Vec::deserialize(deserializer)?;
+        Ok(names.iter().filter_map(|name| Feature::from_name(name)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASWELL: &str = "fpu vme de pse tsc msr pae mce cx8 apic sep mtrr pge mca cmov pat pse36 clflush mmx fxsr \
+        sse sse2 ss ht syscall nx pdpe1gb rdtscp lm constant_tsc pni pclmulqdq ssse3 fma cx16 sse4_1 sse4_2 movbe popcnt \
+        aes xsave avx f16c rdrand lahf_lm abm bmi1 avx2 bmi2";
+
+    #[test]
+    fn hwcap_bits_follow_the_given_architecture() {
+        let features = FeatureSet::from_hwcap(CPUArchitecture::Arm64, 0x0010_0183, 1 << 1);
+        let expected: FeatureSet = [Feature::Fp, Feature::Neon, Feature::Crc32, Feature::Atomics, Feature::DotProd, Feature::Sve2]
+            .into_iter()
+            .collect();
+        assert_eq!(features, expected);
+        assert_eq!(FeatureSet::from_hwcap(CPUArchitecture::X64, 0x0010_0183, 1 << 1), FeatureSet::new());
+    }
+
+    #[test]
+    fn levels_from_cpuinfo_flags() {
+        let haswell = FeatureSet::from_cpuinfo_flags(HASWELL);
+        assert!(!haswell.contains(Feature::Osxsave));
+        assert_eq!(X86_64Level::detect(&haswell), Some(X86_64Level::V3));
+
+        // What the kernel lists after booting with "noxsave"
+        let noxsave = FeatureSet::from_cpuinfo_flags(&HASWELL.replace(" xsave avx", "").replace(" avx2", ""));
+        assert_eq!(X86_64Level::detect(&noxsave), Some(X86_64Level::V2));
+
+        let mut avx512 = haswell.clone();
+        avx512.extend(X86_64Level::V4.required_features().iter().copied());
+        assert_eq!(X86_64Level::detect(&avx512), Some(X86_64Level::V4));
+
+        assert_eq!(X86_64Level::detect(&FeatureSet::from_cpuinfo_flags("fpu sse sse2")), None);
+    }
+}
@@ -1,38 +1,70 @@
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
-use crate::utils::windows::deserialisers::*;
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Debug, Deserialize)]
+pub mod cim;
+pub use cim::CimDateTime;
+pub mod cpuid;
+pub mod error;
+pub use error::Error;
+pub mod features;
+pub use features::{Feature, FeatureSet, X86_64Level};
+pub mod microarch;
+pub mod diff;
+pub mod pci_ids;
+pub mod riscv;
+pub use riscv::RiscvIsa;
+pub mod smbios;
+pub mod snapshot;
+pub use snapshot::SystemSnapshot;
+pub mod units;
+pub use units::{ByteSize, Frequency};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CPUInfo {
-    #[serde(rename = "Manufacturer")]
     pub vendor: String,
-
-    #[serde(rename = "Description")]
     pub model: String,
-
-    #[serde(rename = "Name")]
     pub name: String,
-
-    #[serde(rename = "CurrentClockSpeed", deserialize_with = "format_frequency")]
-    pub frequency: String,
-
-    #[serde(rename = "Architecture", deserialize_with = "deserialize_architecture")]
+    pub frequency: Frequency,
     pub architecture: CPUArchitecture,
-
-    #[serde(rename = "NumberOfCores", deserialize_with = "to_string")]
-    pub cores: String,
-
-    #[serde(rename = "NumberOfLogicalProcessors", deserialize_with = "to_string")]
-    pub logical_cores: String,
-
-    #[serde(flatten)]
+    pub cores: u32,
+    pub logical_cores: u32,
     pub cache_size: CPUCacheSize,
-
-    #[serde(rename = "VirtualizationFirmwareEnabled")]
     pub virtualisation: bool,
+    #[serde(default)]
+    pub features: FeatureSet,
+    #[serde(default)]
+    pub x86_64_level: Option<X86_64Level>, // None for anything but x86-64 processors
+    #[serde(default)]
+    pub microarchitecture: Option<String>, // The core design, e.g. "Zen 3" or "Neoverse V1"
+    #[serde(default)]
+    pub performance_cores: Option<u32>, // The split of `cores` on hybrid processors, None on the rest
+    #[serde(default)]
+    pub efficiency_cores: Option<u32>,
+    #[serde(default)]
+    pub riscv_isa: Option<RiscvIsa>, // RISC-V only, like the address translation mode below
+    #[serde(default)]
+    pub mmu: Option<String>,         // e.g. "sv39"
+}
+
+impl CPUInfo {
+    pub fn features(&self) -> &FeatureSet {
+        &self.features
+    }
+
+    // The highest x86-64 psABI level the processor supports, from its architecture and features
+    pub fn detect_x86_64_level(&self) -> Option<X86_64Level> {
+        match self.architecture {
+            CPUArchitecture::X64 => X86_64Level::detect(&self.features),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CPUArchitecture {
     X86,          // The x86 processor architecture
     Arm,          // The ARM processor architecture
@@ -40,233 +72,919 @@ pub enum CPUArchitecture {
     Neutral,      // A neutral processor architecture
     Arm64,        // The Arm64 processor architecture
     X86OnArm64,   // The Arm64 processor architecture emulating the X86 architecture
+    RiscV32,      // 32-bit RISC-V
+    RiscV64,      // 64-bit RISC-V
+    PowerPC,      // 32-bit POWER / PowerPC
+    PowerPC64,    // 64-bit POWER, either endianness
+    S390x,        // IBM Z
+    LoongArch64,  // 64-bit LoongArch
+    Mips,         // 32-bit MIPS, either endianness
+    Mips64,       // 64-bit MIPS, either endianness
+    Sparc64,      // 64-bit SPARC
+    Ia64,         // Itanium
     Unknown,      // An unknown processor architecture
 }
 
-#[derive(Debug, Deserialize)]
+impl CPUArchitecture {
+    // From a uname(2) machine string such as "x86_64", "aarch64" or "ppc64le". Rust's target
+    // architecture names are understood too.
+    pub fn from_uname(machine: &str) -> CPUArchitecture {
+        match machine.trim().to_ascii_lowercase().as_str() {
+            "x86_64" | "amd64" | "x64" => CPUArchitecture::X64,
+            "x86" | "i386" | "i486" | "i586" | "i686" => CPUArchitecture::X86,
+            "aarch64" | "aarch64_be" | "arm64" => CPUArchitecture::Arm64,
+            arm if arm.starts_with("arm") => CPUArchitecture::Arm,
+            "riscv32" => CPUArchitecture::RiscV32,
+            "riscv64" => CPUArchitecture::RiscV64,
+            "ppc" | "ppcle" | "powerpc" => CPUArchitecture::PowerPC,
+            "ppc64" | "ppc64le" | "powerpc64" => CPUArchitecture::PowerPC64,
+            "s390x" => CPUArchitecture::S390x,
+            "loongarch64" => CPUArchitecture::LoongArch64,
+            "mips" | "mipsel" => CPUArchitecture::Mips,
+            "mips64" | "mips64el" => CPUArchitecture::Mips64,
+            "sparc64" => CPUArchitecture::Sparc64,
+            "ia64" => CPUArchitecture::Ia64,
+            _ => CPUArchitecture::Unknown,
+        }
+    }
+
+    // The architecture this binary was compiled for
+    pub fn current() -> CPUArchitecture {
+        CPUArchitecture::from_uname(std::env::consts::ARCH)
+    }
+
+    // The Linux machine name where there is one, e.g. "x86_64" or "riscv64"
+    pub fn name(self) -> &'static str {
+        match self {
+            CPUArchitecture::X86 => "x86",
+            CPUArchitecture::Arm => "arm",
+            CPUArchitecture::X64 => "x86_64",
+            CPUArchitecture::Neutral => "neutral",
+            CPUArchitecture::Arm64 => "aarch64",
+            CPUArchitecture::X86OnArm64 => "x86_on_arm64",
+            CPUArchitecture::RiscV32 => "riscv32",
+            CPUArchitecture::RiscV64 => "riscv64",
+            CPUArchitecture::PowerPC => "ppc",
+            CPUArchitecture::PowerPC64 => "ppc64",
+            CPUArchitecture::S390x => "s390x",
+            CPUArchitecture::LoongArch64 => "loongarch64",
+            CPUArchitecture::Mips => "mips",
+            CPUArchitecture::Mips64 => "mips64",
+            CPUArchitecture::Sparc64 => "sparc64",
+            CPUArchitecture::Ia64 => "ia64",
+            CPUArchitecture::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for CPUArchitecture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Accepts our own names, uname machine strings and the variant names older snapshots used
+impl FromStr for CPUArchitecture {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "neutral" => Ok(CPUArchitecture::Neutral),
+            "x86_on_arm64" | "x86onarm64" => Ok(CPUArchitecture::X86OnArm64),
+            "unknown" => Ok(CPUArchitecture::Unknown),
+            machine => match CPUArchitecture::from_uname(machine) {
+                CPUArchitecture::Unknown => Err(Error::parse("architecture", format!("unknown architecture '{}'", s))),
+                architecture => Ok(architecture),
+            },
+        }
+    }
+}
+
+impl Serialize for CPUArchitecture {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+// Names this version doesn't know read as Unknown, so newer snapshots still load
+impl<'de> Deserialize<'de> for CPUArchitecture {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(name.parse().unwrap_or(CPUArchitecture::Unknown))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CPUCacheSize {
-    #[serde(rename = "L1CacheSize", default, deserialize_with = "optional_to_string")]
-    pub L1: String,
+    pub l1: Option<ByteSize>,
+    pub l2: Option<ByteSize>,
+    pub l3: Option<ByteSize>,
+}
 
-    #[serde(rename = "L2CacheSize", default, deserialize_with = "to_string")]
-    pub L2: String,
+// How the logical processors are laid out: packages (sockets) contain dies, dies contain
+// physical cores, and cores run one or more hardware threads (logical CPUs).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CPUTopology {
+    pub packages: Vec<CPUPackage>,
+    pub numa_nodes: Vec<NumaNode>,
+}
 
-    #[serde(rename = "L3CacheSize", default, deserialize_with = "to_string")]
-    pub L3: String,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CPUPackage {
+    pub id: u32,
+    pub dies: Vec<CPUDie>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GPUInfo {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CPUDie {
+    pub id: u32,
+    pub cores: Vec<CPUCore>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CPUCore {
+    pub id: u32,              // Only unique within its die, or its cluster on ARM
+    pub threads: Vec<u32>,    // Logical CPU numbers, as used for affinity masks
+    pub numa_node: Option<u32>,
     #[serde(default)]
+    pub kind: Option<CoreKind>, // None unless the processor mixes core designs
+}
+
+// The two classes of core on hybrid processors: Intel's P-cores and E-cores, or the big and
+// LITTLE clusters on ARM. Three-tier ARM designs count every cluster above the smallest as
+// performance cores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoreKind {
+    Performance,
+    Efficiency,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumaNode {
+    pub id: u32,
+    pub cpus: Vec<u32>,
+    pub memory: Option<ByteSize>,
+}
+
+impl CPUTopology {
+    pub fn cores(&self) -> impl Iterator<Item = &CPUCore> {
+        self.packages.iter().flat_map(|package| &package.dies).flat_map(|die| &die.cores)
+    }
+
+    pub fn physical_cores(&self) -> usize {
+        self.cores().count()
+    }
+
+    pub fn logical_cpus(&self) -> usize {
+        self.cores().map(|core| core.threads.len()).sum()
+    }
+
+    // The first hardware thread of every physical core, for placing one worker per core
+    pub fn primary_threads(&self) -> Vec<u32> {
+        self.cores().filter_map(|core| core.threads.first().copied()).collect()
+    }
+
+    pub fn is_hybrid(&self) -> bool {
+        self.cores().any(|core| core.kind.is_some())
+    }
+
+    // Physical cores per class, empty on processors that aren't hybrid
+    pub fn core_counts(&self) -> BTreeMap<CoreKind, usize> {
+        let mut counts = BTreeMap::new();
+        for kind in self.cores().filter_map(|core| core.kind) {
+            *counts.entry(kind).or_default() += 1;
+        }
+        counts
+    }
+
+    // Every logical CPU on cores of one class, for pinning latency-critical threads to the
+    // performance cores
+    pub fn threads_of_kind(&self, kind: CoreKind) -> Vec<u32> {
+        self.cores().filter(|core| core.kind == Some(kind)).flat_map(|core| core.threads.iter().copied()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GPUInfo {
     index: u8,
-    #[serde(rename = "AdapterCompatibility")]
     vendor: String,
-    #[serde(rename = "Name")]
     model: String,
-    #[serde(rename = "AdapterRAM")]
     memory: u128,
-    #[serde(rename = "DeviceID")]
-    device_id: String,
-    #[serde(flatten)]
+    device_id: String, // The PCI slot on Linux, the WMI instance name (e.g. "VideoController1") on Windows
+    #[serde(default)]
+    pnp_device_id: Option<String>, // Windows only, e.g. "PCI\VEN_1002&DEV_73BF&SUBSYS_E4381DA2&REV_C1\..."
     refresh_rate: GPURefreshRate,
-    #[serde(rename = "InstalledDisplayDrivers", deserialize_with = "deserialize_drivers")]
     display_drivers_location: Vec<String>,
-    #[serde(rename = "DriverVersion")]
     driver_version: String,
-    #[serde(rename = "VideoModeDescription", deserialize_with = "deserialize_video_modes")]
     video_mode_description: Vec<String>,
-    #[serde(rename = "Status", deserialize_with = "deserialize_status")]
     status: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GPURefreshRate {
-    #[serde(rename = "MinRefreshRate")]
     min: u32,
-    #[serde(rename = "MaxRefreshRate")]
     max: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OSInfo {
-    #[serde(rename = "Name")]
     name: String,
-    #[serde(rename = "Caption")]
     short_name: String,
-    #[serde(rename = "Version")]
     version: String,
-    #[serde(rename = "OSArchitecture")]
     os_architecture: String,
-    #[serde(rename = "Status")]
     status: String,
-    #[serde(rename = "CSName")]
     computer_name: String,
-    #[serde(rename = "LastBootUpTime", deserialize_with = "deserialize_last_boot_up_time")]
-    uptime: NaiveDateTime,
+    #[serde(alias = "uptime", deserialize_with = "deserialize_boot_time")]
+    boot_time: DateTime<Utc>,
+}
+
+impl OSInfo {
+    pub fn boot_time(&self) -> DateTime<Utc> {
+        self.boot_time
+    }
+
+    // Time since boot, zero if the clock has been set back before the boot time since
+    pub fn uptime(&self) -> Duration {
+        (Utc::now() - self.boot_time).to_std().unwrap_or_default()
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// Snapshots written before boot_time existed stored the boot timestamp as a naive "uptime"
+fn deserialize_boot_time<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    value
+        .parse::<DateTime<Utc>>()
+        .or_else(|_| value.parse::<NaiveDateTime>().map(|naive| naive.and_utc()))
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemInfo {
-    #[serde(default)]
     index: u8,
-    #[serde(rename = "Manufacturer", default)]
     vendor: String,
-    #[serde(rename = "Model", default)]
     model: String,
-    #[serde(rename = "DeviceLocator", default)]
     name: String,
-    #[serde(rename = "SerialNumber", default)]
     serial_number: String,
-    #[serde(rename = "PartNumber", default)]
     part_number: String,
-    #[serde(rename = "Capacity", deserialize_with = "deserialize_capacity")]
-    total_memory: u64,
     #[serde(default)]
-    free_memory: u64, // This field doesn't exist in WMI, so we'll leave it as default (0).
+    speed_mts: Option<u32>, // The configured transfer rate, None where the firmware doesn't report one
+    total_memory: u64,
+    free_memory: u64, // System-wide available memory (it isn't tracked per module), 0 where the OS doesn't report it.
+}
+
+// System-wide memory usage at the time of the fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    pub total: ByteSize,              // Physical memory usable by the OS
+    pub available: ByteSize,          // What can be handed out without swapping, including reclaimable caches
+    pub free: Option<ByteSize>,       // Completely unused, None where the OS only reports available
+    pub cached: Option<ByteSize>,     // Page cache, None where the OS doesn't report it separately
+    pub buffers: Option<ByteSize>,    // Block device buffers, Linux only
+    pub swap_total: ByteSize,
+    pub swap_used: ByteSize,
+    pub committed: Option<ByteSize>,  // Memory promised to processes, which can exceed what's resident
+    pub commit_limit: Option<ByteSize>,
 }
 
-#[cfg(target_os = "windows")]
 pub mod windows {
     use std::collections::HashMap;
-    use crate::{CPUInfo, GPUInfo, GPURefreshRate, MemInfo, OSInfo};
+    use std::path::Path;
+    use chrono::{DateTime, Utc};
+    use serde::de::DeserializeOwned;
+    use serde::Deserialize;
+    use serde_json::{Map, Value};
+    use crate::utils::windows::deserialisers::*;
+    use crate::{ByteSize, CPUArchitecture, CPUCacheSize, CPUInfo, CPUTopology, Error, FeatureSet, Frequency, GPUInfo, GPURefreshRate, MemInfo, MemoryUsage, OSInfo};
+    #[cfg(target_os = "windows")]
     use wmi::*;
+
+    // A single WMI result row, keyed by property name
+    pub type Row = Map<String, Value>;
+
+    // Anything that can answer a WQL query with rows of variants. The mapping from rows to our
+    // structs only goes through this trait, so it can be exercised against recorded dumps.
+    pub trait QuerySource {
+        fn raw_query(&self, query: &str) -> Result<Vec<Row>, Error>;
+    }
+
+    // The live WMI service
+    #[cfg(target_os = "windows")]
+    pub struct WmiSource {
+        connection: WMIConnection,
+    }
+
+    #[cfg(target_os = "windows")]
+    impl WmiSource {
+        pub fn new() -> Result<Self, Error> {
+            let com_lib = COMLibrary::new().map_err(|err| Error::SourceUnavailable {
+                source: "COM Library".to_string(),
+                reason: err.to_string(),
+            })?;
+            let connection = WMIConnection::new(com_lib).map_err(|err| Error::SourceUnavailable {
+                source: "WMI".to_string(),
+                reason: err.to_string(),
+            })?;
+            Ok(WmiSource { connection })
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    impl QuerySource for WmiSource {
+        fn raw_query(&self, query: &str) -> Result<Vec<Row>, Error> {
+            let results: Vec<HashMap<String, Variant>> = self.connection
+                .raw_query(query)
+                .map_err(|err| Error::SourceUnavailable {
+                    source: query.to_string(),
+                    reason: err.to_string(),
+                })?;
+            Ok(results
+                .into_iter()
+                .map(|row| row.into_iter().map(|(key, value)| (key, variant_to_value(value))).collect())
+                .collect())
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn variant_to_value(variant: Variant) -> Value {
+        match variant {
+            Variant::String(s) => Value::from(s),
+            Variant::I1(n) => Value::from(n),
+            Variant::I2(n) => Value::from(n),
+            Variant::I4(n) => Value::from(n),
+            Variant::I8(n) => Value::from(n),
+            Variant::R4(n) => Value::from(n),
+            Variant::R8(n) => Value::from(n),
+            Variant::Bool(b) => Value::from(b),
+            Variant::UI1(n) => Value::from(n),
+            Variant::UI2(n) => Value::from(n),
+            Variant::UI4(n) => Value::from(n),
+            Variant::UI8(n) => Value::from(n),
+            Variant::Array(items) => Value::Array(items.into_iter().map(variant_to_value).collect()),
+            _ => Value::Null,
+        }
+    }
+
+    // Replays recorded WMI dumps. The JSON document maps a WMI class name to its rows, e.g.
+    // { "Win32_Processor": [ { "Manufacturer": "GenuineIntel", ... } ] }
+    #[derive(Debug, Clone, Default)]
+    pub struct FixtureSource {
+        classes: HashMap<String, Vec<Row>>,
+    }
+
+    impl FixtureSource {
+        pub fn from_json(json: &str) -> Result<Self, Error> {
+            let classes = serde_json::from_str(json).map_err(|err| Error::parse("fixture", err.to_string()))?;
+            Ok(FixtureSource { classes })
+        }
+
+        pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+            let path = path.as_ref();
+            let json = std::fs::read_to_string(path).map_err(|err| Error::io(path.display().to_string(), err))?;
+            Self::from_json(&json)
+        }
+
+        // Captures the given classes from another source, e.g. to record a dump from a live machine
+        pub fn record<S: QuerySource + ?Sized>(source: &S, classes: &[&str]) -> Result<Self, Error> {
+            let mut fixture = FixtureSource::default();
+            for class in classes {
+                let rows = source.raw_query(&format!("SELECT * FROM {}", class))?;
+                fixture.classes.insert(class.to_string(), rows);
+            }
+            Ok(fixture)
+        }
+
+        pub fn to_json(&self) -> String {
+            serde_json::to_string_pretty(&self.classes).unwrap_or_default()
+        }
+    }
+
+    impl QuerySource for FixtureSource {
+        fn raw_query(&self, query: &str) -> Result<Vec<Row>, Error> {
+            let class = query_class(query).ok_or_else(|| Error::parse("query", query))?;
+            self.classes
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(class))
+                .map(|(_, rows)| rows.clone())
+                .ok_or_else(|| Error::SourceUnavailable {
+                    source: class.to_string(),
+                    reason: "class not present in fixture".to_string(),
+                })
+        }
+    }
+
+    // Extracts the class name from a "SELECT ... FROM <class> [WHERE ...]" query
+    fn query_class(query: &str) -> Option<&str> {
+        let mut words = query.split_whitespace();
+        words.find(|word| word.eq_ignore_ascii_case("FROM"))?;
+        words.next()
+    }
+
+    // The WMI classes we read, mapped column by column before being converted into the
+    // platform-neutral structs above.
+    #[derive(Deserialize)]
+    struct Win32Processor {
+        #[serde(rename = "Manufacturer")]
+        vendor: String,
+
+        #[serde(rename = "Description")]
+        model: String,
+
+        #[serde(rename = "Name")]
+        name: String,
+
+        #[serde(rename = "CurrentClockSpeed", deserialize_with = "deserialize_frequency")]
+        frequency: Frequency,
+
+        #[serde(rename = "Architecture", deserialize_with = "deserialize_architecture")]
+        architecture: CPUArchitecture,
+
+        #[serde(rename = "NumberOfCores")]
+        cores: u32,
+
+        #[serde(rename = "NumberOfLogicalProcessors")]
+        logical_cores: u32,
+
+        #[serde(rename = "L1CacheSize", default, deserialize_with = "deserialize_cache_size")]
+        l1_cache_size: Option<ByteSize>,
+
+        #[serde(rename = "L2CacheSize", default, deserialize_with = "deserialize_cache_size")]
+        l2_cache_size: Option<ByteSize>,
+
+        #[serde(rename = "L3CacheSize", default, deserialize_with = "deserialize_cache_size")]
+        l3_cache_size: Option<ByteSize>,
+
+        #[serde(rename = "VirtualizationFirmwareEnabled")]
+        virtualisation: bool,
+    }
+
+    impl From<Win32Processor> for CPUInfo {
+        fn from(processor: Win32Processor) -> Self {
+            // Description reads e.g. "Intel64 Family 6 Model 158 Stepping 10"
+            let microarchitecture = crate::microarch::parse_x86_description(&processor.model)
+                .and_then(|(family, model, stepping)| crate::microarch::x86(&processor.vendor, family, model, stepping))
+                .map(String::from);
+            CPUInfo {
+                vendor: processor.vendor,
+                model: processor.model,
+                name: processor.name,
+                frequency: processor.frequency,
+                architecture: processor.architecture,
+                cores: processor.cores,
+                logical_cores: processor.logical_cores,
+                cache_size: CPUCacheSize {
+                    l1: processor.l1_cache_size,
+                    l2: processor.l2_cache_size,
+                    l3: processor.l3_cache_size,
+                },
+                virtualisation: processor.virtualisation,
+                features: FeatureSet::new(), // Filled in from CPUID by fetch
+                x86_64_level: None,
+                microarchitecture,
+                performance_cores: None,
+                efficiency_cores: None,
+                riscv_isa: None,
+                mmu: None,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Win32VideoController {
+        #[serde(rename = "AdapterCompatibility")]
+        vendor: String,
+        #[serde(rename = "Name")]
+        model: String,
+        #[serde(rename = "AdapterRAM")]
+        memory: u128,
+        #[serde(rename = "DeviceID")]
+        device_id: String,
+        #[serde(rename = "PNPDeviceID", default)]
+        pnp_device_id: Option<String>,
+        #[serde(rename = "MinRefreshRate")]
+        min_refresh_rate: u32,
+        #[serde(rename = "MaxRefreshRate")]
+        max_refresh_rate: u32,
+        #[serde(rename = "InstalledDisplayDrivers", deserialize_with = "deserialize_drivers")]
+        display_drivers_location: Vec<String>,
+        #[serde(rename = "DriverVersion")]
+        driver_version: String,
+        #[serde(rename = "VideoModeDescription", deserialize_with = "deserialize_video_modes")]
+        video_mode_description: Vec<String>,
+        #[serde(rename = "Status", deserialize_with = "deserialize_status")]
+        status: bool,
+    }
+
+    impl From<Win32VideoController> for GPUInfo {
+        fn from(controller: Win32VideoController) -> Self {
+            GPUInfo {
+                index: 0,
+                vendor: controller.vendor,
+                model: controller.model,
+                memory: controller.memory,
+                device_id: controller.device_id,
+                pnp_device_id: controller.pnp_device_id.filter(|id| !id.is_empty()),
+                refresh_rate: GPURefreshRate {
+                    min: controller.min_refresh_rate,
+                    max: controller.max_refresh_rate,
+                },
+                display_drivers_location: controller.display_drivers_location,
+                driver_version: controller.driver_version,
+                video_mode_description: controller.video_mode_description,
+                status: controller.status,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Win32OperatingSystem {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Caption")]
+        short_name: String,
+        #[serde(rename = "Version")]
+        version: String,
+        #[serde(rename = "OSArchitecture")]
+        os_architecture: String,
+        #[serde(rename = "Status")]
+        status: String,
+        #[serde(rename = "CSName")]
+        computer_name: String,
+        #[serde(rename = "LastBootUpTime", deserialize_with = "deserialize_last_boot_up_time")]
+        boot_time: DateTime<Utc>,
+    }
+
+    impl From<Win32OperatingSystem> for OSInfo {
+        fn from(os: Win32OperatingSystem) -> Self {
+            OSInfo {
+                name: os.name,
+                short_name: os.short_name,
+                version: os.version,
+                os_architecture: os.os_architecture,
+                status: os.status,
+                computer_name: os.computer_name,
+                boot_time: os.boot_time,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Win32PhysicalMemory {
+        #[serde(rename = "Manufacturer", default)]
+        vendor: String,
+        #[serde(rename = "Model", default)]
+        model: String,
+        #[serde(rename = "DeviceLocator", default)]
+        name: String,
+        #[serde(rename = "SerialNumber", default)]
+        serial_number: String,
+        #[serde(rename = "PartNumber", default)]
+        part_number: String,
+        #[serde(rename = "ConfiguredClockSpeed", default)]
+        configured_speed: Option<u32>,
+        #[serde(rename = "Speed", default)]
+        speed: Option<u32>,
+        #[serde(rename = "Capacity", deserialize_with = "deserialize_capacity")]
+        total_memory: u64,
+    }
+
+    impl From<Win32PhysicalMemory> for MemInfo {
+        fn from(memory: Win32PhysicalMemory) -> Self {
+            MemInfo {
+                index: 0,
+                vendor: memory.vendor,
+                model: memory.model,
+                name: memory.name,
+                serial_number: memory.serial_number,
+                part_number: memory.part_number,
+                speed_mts: memory.configured_speed.or(memory.speed).filter(|&speed| speed != 0),
+                total_memory: memory.total_memory,
+                free_memory: 0, // Filled in from Win32_OperatingSystem by fetch_from_source
+            }
+        }
+    }
+
+    // The memory counters of Win32_OperatingSystem, all in KB. WMI hands uint64 properties over
+    // as strings, so these go through the same parsing as Win32_PhysicalMemory's capacity.
+    #[derive(Deserialize)]
+    struct Win32OperatingSystemMemory {
+        #[serde(rename = "TotalVisibleMemorySize", deserialize_with = "deserialize_kib")]
+        total: ByteSize,
+        #[serde(rename = "FreePhysicalMemory", deserialize_with = "deserialize_kib")]
+        free: ByteSize,
+        #[serde(rename = "TotalVirtualMemorySize", default, deserialize_with = "deserialize_optional_kib")]
+        virtual_total: Option<ByteSize>,
+        #[serde(rename = "FreeVirtualMemory", default, deserialize_with = "deserialize_optional_kib")]
+        virtual_free: Option<ByteSize>,
+        #[serde(rename = "SizeStoredInPagingFiles", default, deserialize_with = "deserialize_optional_kib")]
+        paging_total: Option<ByteSize>,
+        #[serde(rename = "FreeSpaceInPagingFiles", default, deserialize_with = "deserialize_optional_kib")]
+        paging_free: Option<ByteSize>,
+    }
+
+    impl From<Win32OperatingSystemMemory> for MemoryUsage {
+        fn from(memory: Win32OperatingSystemMemory) -> Self {
+            let used = |total: Option<ByteSize>, free: Option<ByteSize>| {
+                ByteSize::from_bytes(total.unwrap_or_default().bytes().saturating_sub(free.unwrap_or_default().bytes()))
+            };
+            MemoryUsage {
+                total: memory.total,
+                // FreePhysicalMemory already counts the standby list, which is what Task Manager calls available.
+                // The zeroed and free lists on their own aren't exposed by Win32_OperatingSystem.
+                available: memory.free,
+                free: None,
+                cached: None,
+                buffers: None,
+                swap_total: memory.paging_total.unwrap_or_default(),
+                swap_used: used(memory.paging_total, memory.paging_free),
+                // The virtual memory totals are the commit limit and what's left of it
+                committed: memory.virtual_total.map(|_| used(memory.virtual_total, memory.virtual_free)),
+                commit_limit: memory.virtual_total,
+            }
+        }
+    }
+
+    fn query<T: DeserializeOwned, S: QuerySource + ?Sized>(source: &S, class: &str) -> Result<Vec<T>, Error> {
+        source
+            .raw_query(&format!("SELECT * FROM {}", class))?
+            .into_iter()
+            .map(|row| serde_json::from_value(Value::Object(row)).map_err(|err| Error::parse(class, err.to_string())))
+            .collect()
+    }
+
     #[allow(missing_copy_implementations)]
     impl CPUInfo {
         #[cfg(target_os = "windows")]
-        pub fn fetch() -> Vec<CPUInfo> {
-            let wmi_con = WMIConnection::new(
-                COMLibrary::new().unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while accessing the COM Library: {}", err)))
-                .unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while connecting to the WMI: {}", err));
-            let results: Vec<CPUInfo> = wmi_con.raw_query("SELECT * FROM Win32_Processor").unwrap();
-            results
+        pub fn fetch() -> Result<Vec<CPUInfo>, Error> {
+            let mut cpus = Self::fetch_from_source(&WmiSource::new()?)?;
+            // WMI reports no instruction set extensions and often no L1 cache size
+            let cpuid = crate::cpuid::Cpuid::read();
+            for cpu in &mut cpus {
+                if let Some(cpuid) = &cpuid {
+                    cpu.fill_from_cpuid(cpuid);
+                }
+                cpu.x86_64_level = cpu.detect_x86_64_level();
+            }
+            Ok(cpus)
+        }
+
+        pub fn fetch_from_source<S: QuerySource + ?Sized>(source: &S) -> Result<Vec<CPUInfo>, Error> {
+            let results: Vec<Win32Processor> = query(source, "Win32_Processor")?;
+            Ok(results.into_iter().map(CPUInfo::from).collect())
+        }
+    }
+
+    // WMI has no per-thread view of the processors, that needs GetLogicalProcessorInformationEx
+    impl CPUTopology {
+        #[cfg(target_os = "windows")]
+        pub fn fetch() -> Result<CPUTopology, Error> {
+            Err(Error::unsupported())
         }
     }
 
     impl GPUInfo {
         #[cfg(target_os = "windows")]
-        pub fn fetch() -> Vec<GPUInfo> {
-            // Initialize COM library
-            let wmi_con = WMIConnection::new(
-                COMLibrary::new().unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while accessing the COM Library: {}", err)))
-                .unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while connecting to the WMI: {}", err));
-            let mut results: Vec<GPUInfo> = wmi_con.raw_query("SELECT * FROM Win32_VideoController").unwrap();
+        pub fn fetch() -> Result<Vec<GPUInfo>, Error> {
+            Self::fetch_from_source(&WmiSource::new()?)
+        }
+
+        pub fn fetch_from_source<S: QuerySource + ?Sized>(source: &S) -> Result<Vec<GPUInfo>, Error> {
+            let results: Vec<Win32VideoController> = query(source, "Win32_VideoController")?;
+            let mut results: Vec<GPUInfo> = results.into_iter().map(GPUInfo::from).collect();
 
             for (i, gpu) in results.iter_mut().enumerate() {
                 gpu.index = i as u8;
             }
 
-            results
+            Ok(results)
         }
     }
     impl OSInfo {
         #[cfg(target_os = "windows")]
-        pub fn fetch() -> Vec<OSInfo> {
-            // Initialize COM library
-            let wmi_con = WMIConnection::new(
-                COMLibrary::new().unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while accessing the COM Library: {}", err)))
-                .unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while connecting to the WMI: {}", err));
-            let mut results: Vec<OSInfo> = wmi_con.raw_query("SELECT * FROM Win32_OperatingSystem").unwrap();
+        pub fn fetch() -> Result<Vec<OSInfo>, Error> {
+            Self::fetch_from_source(&WmiSource::new()?)
+        }
 
-            results
+        pub fn fetch_from_source<S: QuerySource + ?Sized>(source: &S) -> Result<Vec<OSInfo>, Error> {
+            let results: Vec<Win32OperatingSystem> = query(source, "Win32_OperatingSystem")?;
+            Ok(results.into_iter().map(OSInfo::from).collect())
         }
     }
 
     impl MemInfo {
         #[cfg(target_os = "windows")]
-        pub fn fetch() -> Result<Vec<MemInfo>, String> {
-            // Initialize COM library
-            let wmi_con = WMIConnection::new(COMLibrary::new().map_err(|err| {
-                format!("Failed to initialize COM Library: {}", err)
-            })?)
-                .map_err(|err| format!("Failed to connect to WMI: {}", err))?;
+        pub fn fetch() -> Result<Vec<MemInfo>, Error> {
+            Self::fetch_from_source(&WmiSource::new()?)
+        }
 
+        pub fn fetch_from_source<S: QuerySource + ?Sized>(source: &S) -> Result<Vec<MemInfo>, Error> {
             // Query for physical memory
-            let results: Vec<MemInfo> = wmi_con
-                .raw_query("SELECT * FROM Win32_PhysicalMemory")
-                .map_err(|err| format!("WMI query failed: {}", err))?;
+            let results: Vec<Win32PhysicalMemory> = query(source, "Win32_PhysicalMemory")?;
+            let mut results: Vec<MemInfo> = results.into_iter().map(MemInfo::from).collect();
+
+            // Free memory is only known system-wide, so every module carries the same value
+            let available = MemoryUsage::fetch_from_source(source)
+                .map(|usage| usage.available.bytes())
+                .unwrap_or(0);
 
             // Add indices to each memory module
-            let mut results = results;
             for (i, mem) in results.iter_mut().enumerate() {
                 mem.index = i as u8;
+                mem.free_memory = available;
             }
 
             Ok(results)
         }
     }
 
-}
+    impl MemoryUsage {
+        #[cfg(target_os = "windows")]
+        pub fn fetch() -> Result<MemoryUsage, Error> {
+            Self::fetch_from_source(&WmiSource::new()?)
+        }
+
+        pub fn fetch_from_source<S: QuerySource + ?Sized>(source: &S) -> Result<MemoryUsage, Error> {
+            let results: Vec<Win32OperatingSystemMemory> = query(source, "Win32_OperatingSystem")?;
+            results
+                .into_iter()
+                .next()
+                .map(MemoryUsage::from)
+                .ok_or_else(|| Error::parse("Win32_OperatingSystem", "no rows"))
+        }
+    }
 
-pub mod linux {
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-}
+        fn fixture() -> FixtureSource {
+            FixtureSource::from_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/windows/desktop.json")).unwrap()
+        }
+
+        #[test]
+        fn processor_mapping() {
+            let cpus = CPUInfo::fetch_from_source(&fixture()).unwrap();
+            assert_eq!(cpus.len(), 1);
+            let cpu = &cpus[0];
+            assert_eq!(cpu.vendor, "GenuineIntel");
+            assert_eq!(cpu.name, "Intel(R) Core(TM) i3-9100F CPU @ 3.60GHz");
+            assert_eq!(cpu.architecture, CPUArchitecture::X64);
+            assert_eq!(cpu.frequency, Frequency::from_mhz(3600));
+            assert_eq!((cpu.cores, cpu.logical_cores), (4, 4));
+            assert_eq!(cpu.cache_size.l1, None);
+            assert_eq!(cpu.cache_size.l2, Some(ByteSize::from_kib(1024)));
+            assert_eq!(cpu.cache_size.l3, Some(ByteSize::from_kib(6144)));
+            assert!(cpu.virtualisation);
+            assert_eq!(cpu.microarchitecture.as_deref(), Some("Coffee Lake"));
+        }
+
+        #[test]
+        fn video_controller_mapping() {
+            let gpus = GPUInfo::fetch_from_source(&fixture()).unwrap();
+            assert_eq!(gpus.iter().map(|gpu| gpu.index).collect::<Vec<_>>(), [0, 1]);
+            let gpu = &gpus[0];
+            assert_eq!(gpu.vendor, "Advanced Micro Devices, Inc.");
+            assert_eq!(gpu.model, "AMD Radeon RX 6800");
+            assert_eq!(gpu.device_id, "VideoController1");
+            assert_eq!(gpu.pnp_device_id.as_deref(), Some("PCI\\VEN_1002&DEV_73BF&SUBSYS_E4381DA2&REV_C1\\6&2A7B3C1D&0&00000019"));
+            assert_eq!(gpu.memory, 4_293_918_720);
+            assert_eq!((gpu.refresh_rate.min, gpu.refresh_rate.max), (60, 165));
+            assert_eq!(gpu.display_drivers_location.len(), 2);
+            assert_eq!(gpu.video_mode_description, ["2560 x 1440 x 4294967296 colors"]);
+            assert!(gpu.status);
+            assert!(!gpus[1].status);
+        }
+
+        #[test]
+        fn operating_system_mapping() {
+            let os = OSInfo::fetch_from_source(&fixture()).unwrap();
+            let os = &os[0];
+            assert_eq!(os.short_name, "Microsoft Windows 11 Pro");
+            assert_eq!(os.version, "10.0.22631");
+            assert_eq!(os.os_architecture, "64-bit");
+            assert_eq!(os.computer_name, "WORKSTATION");
+            // LastBootUpTime is local time at UTC+2
+            assert_eq!(os.boot_time().to_rfc3339(), "2024-06-01T10:00:00.500+00:00");
+        }
+
+        #[test]
+        fn physical_memory_mapping() {
+            let modules = MemInfo::fetch_from_source(&fixture()).unwrap();
+            assert_eq!(modules.len(), 2);
+            assert_eq!((modules[0].index, modules[1].index), (0, 1));
+            assert_eq!(modules[1].name, "DIMM2");
+            assert_eq!(modules[0].vendor, "Kingston");
+            assert_eq!(modules[0].part_number, "KF432C16BB/8");
+            assert_eq!(modules[0].speed_mts, Some(3200));
+            assert_eq!(modules[0].total_memory, 8 * 1024 * 1024 * 1024);
+            assert_eq!(modules[0].free_memory, 9_634_704 * 1024);
+        }
 
-pub mod macos {
+        #[test]
+        fn operating_system_memory_mapping() {
+            let usage = MemoryUsage::fetch_from_source(&fixture()).unwrap();
+            assert_eq!(usage.total, ByteSize::from_kib(16_318_924));
+            assert_eq!(usage.available, ByteSize::from_kib(9_634_704));
+            assert_eq!(usage.free, None);
+            assert_eq!(usage.swap_used, ByteSize::from_kib(2_097_148 - 1_572_860));
+            assert_eq!(usage.commit_limit, Some(ByteSize::from_kib(18_416_072)));
+            assert_eq!(usage.committed, Some(ByteSize::from_kib(18_416_072 - 7_853_760)));
+        }
 
+        #[test]
+        fn missing_class_is_source_unavailable() {
+            let source = FixtureSource::from_json("{}").unwrap();
+            assert!(matches!(CPUInfo::fetch_from_source(&source), Err(Error::SourceUnavailable { .. })));
+        }
+    }
 }
 
+pub mod linux;
 
+// macOS and every other OS without a backend. The collectors still exist so callers build
+// everywhere, but they report UnsupportedPlatform.
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod unsupported {
+    use crate::{CPUInfo, CPUTopology, Error, GPUInfo, MemInfo, MemoryUsage, OSInfo};
+
+    impl CPUInfo {
+        pub fn fetch() -> Result<Vec<CPUInfo>, Error> {
+            Err(Error::unsupported())
+        }
+    }
+
+    impl CPUTopology {
+        pub fn fetch() -> Result<CPUTopology, Error> {
+            Err(Error::unsupported())
+        }
+    }
+
+    impl GPUInfo {
+        pub fn fetch() -> Result<Vec<GPUInfo>, Error> {
+            Err(Error::unsupported())
+        }
+    }
+
+    impl OSInfo {
+        pub fn fetch() -> Result<Vec<OSInfo>, Error> {
+            Err(Error::unsupported())
+        }
+    }
+
+    impl MemInfo {
+        pub fn fetch() -> Result<Vec<MemInfo>, Error> {
+            Err(Error::unsupported())
+        }
+    }
+
+    impl MemoryUsage {
+        pub fn fetch() -> Result<MemoryUsage, Error> {
+            Err(Error::unsupported())
+        }
+    }
+}
 
 pub mod utils {
+    #[cfg(target_os = "windows")]
     pub mod testing {
-        use std::collections::HashMap;
-        use wmi::{COMLibrary, Variant, WMIConnection};
-        use crate::OSInfo;
+        use crate::windows::{FixtureSource, WmiSource};
 
+        // Prints the rows of a WMI class in the same JSON layout FixtureSource replays
         pub fn fetch_query_based(query: &str) {
-            let wmi_con = WMIConnection::new(
-                COMLibrary::new().unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while accessing the COM Library: {}", err)))
-                .unwrap_or_else(
-                    |err|
-                        panic!("An error occurred while connecting to the WMI: {}", err));
-            let results: Vec<HashMap<String, Variant>> = wmi_con.raw_query(format!("SELECT * FROM {}", query)).unwrap();
-            println!("{:#?}", results);
+            let source = WmiSource::new().unwrap_or_else(
+                |err|
+                    panic!("An error occurred while connecting to the WMI: {}", err));
+            let fixture = FixtureSource::record(&source, &[query]).unwrap();
+            println!("{}", fixture.to_json());
         }
     }
     pub mod windows {
         pub(crate) mod deserialisers {
-            use std::time::Duration;
-            use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+            use chrono::{DateTime, Utc};
             use serde::{Deserialize, Deserializer};
             use serde_json::Value;
-            use crate::CPUArchitecture;
+            use crate::cim::CimTimestamp;
+            use crate::{ByteSize, CPUArchitecture, Frequency};
 
-            pub(crate) fn to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+            // WMI reports clock speeds in MHz
+            pub(crate) fn deserialize_frequency<'de, D>(deserializer: D) -> Result<Frequency, D::Error>
             where
                 D: Deserializer<'de>,
             {
                 let value: u32 = Deserialize::deserialize(deserializer)?;
-                Ok(value.to_string())
+                Ok(Frequency::from_mhz(value as u64))
             }
 
-            pub(crate) fn optional_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+            // WMI reports cache sizes in KB, using null or 0 when the cache level doesn't exist
+            pub(crate) fn deserialize_cache_size<'de, D>(deserializer: D) -> Result<Option<ByteSize>, D::Error>
             where
                 D: Deserializer<'de>,
             {
                 let value: Option<u32> = Option::deserialize(deserializer)?;
-                Ok(value.map_or_else(|| "N/A".to_string(), |v| v.to_string()))
-            }
-
-            pub(crate) fn format_frequency<'de, D>(deserializer: D) -> Result<String, D::Error>
-            where
-                D: Deserializer<'de>,
-            {
-                let value: u32 = Deserialize::deserialize(deserializer)?;
-                Ok(format!("{} MHz", value))
+                Ok(value.filter(|&kb| kb > 0).map(|kb| ByteSize::from_kib(kb as u64)))
             }
 
             pub(crate) fn deserialize_architecture<'de, D>(deserializer: D) -> Result<CPUArchitecture, D::Error>
@@ -307,6 +1025,7 @@ pub mod utils {
                 Ok(status == "OK")
             }
 
+            #[allow(dead_code)]
             pub(crate) fn deserialize_name<'de, D>(deserializer: D) -> Result<String, D::Error>
             where
                 D: Deserializer<'de>,
@@ -334,6 +1053,25 @@ pub mod utils {
                 }
             }
 
+            // Memory counters in KB, which WMI reports as uint64 strings
+            pub(crate) fn deserialize_kib<'de, D>(deserializer: D) -> Result<ByteSize, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserialize_capacity(deserializer).map(ByteSize::from_kib)
+            }
+
+            pub(crate) fn deserialize_optional_kib<'de, D>(deserializer: D) -> Result<Option<ByteSize>, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                match Option::<Value>::deserialize(deserializer)? {
+                    None | Some(Value::Null) => Ok(None),
+                    Some(value) => deserialize_kib(value).map(Some).map_err(serde::de::Error::custom),
+                }
+            }
+
+            #[allow(dead_code)]
             pub(crate) fn default_endian() -> String {
                 if cfg!(target_endian = "little") {
                     String::from("little")
@@ -344,28 +1082,33 @@ pub mod utils {
                 }
             }
 
-            pub(crate) fn deserialize_short_name<'de, D>(deserializer: D) -> Result<String, D::Error>
+            #[allow(dead_code)]
+            pub(crate) fn deserialize_short_name<'de, D>(_deserializer: D) -> Result<String, D::Error>
             where
                 D: Deserializer<'de>,
             {
                 Ok("windows".to_string())
             }
 
-            // Custom deserializer for last boot up time
-            pub(crate) fn deserialize_last_boot_up_time<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+            // WMI timestamps such as "20240105083012.500000+060", converted to UTC
+            pub(crate) fn deserialize_last_boot_up_time<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
             where
                 D: serde::Deserializer<'de>,
             {
-                let time_str: String = String::deserialize(deserializer)?;
-                let (date, _) = time_str.split_once('.').unwrap();
-                NaiveDateTime::parse_from_str(date, "%Y%m%d%H%M%S").map_err(serde::de::Error::custom)
+                let timestamp = CimTimestamp::deserialize(deserializer)?;
+                timestamp
+                    .to_utc()
+                    .ok_or_else(|| serde::de::Error::custom(format!("incomplete CIM_DATETIME '{}'", timestamp)))
             }
 
             impl From<u16> for CPUArchitecture {
                 fn from(value: u16) -> Self {
                     match value {
                         0 => CPUArchitecture::X86,
+                        1 => CPUArchitecture::Mips,
+                        3 => CPUArchitecture::PowerPC,
                         5 => CPUArchitecture::Arm,
+                        6 => CPUArchitecture::Ia64,
                         9 => CPUArchitecture::X64,
                         11 => CPUArchitecture::Neutral,
                         12 => CPUArchitecture::Arm64,
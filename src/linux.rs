@@ -0,0 +1,1080 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use chrono::DateTime;
+use crate::cpuid::Cpuid;
+use crate::microarch;
+use crate::pci_ids::{parse_hex_id, PciIds};
+use crate::smbios::Smbios;
+use crate::{ByteSize, CPUArchitecture, CPUCacheSize, CPUCore, CPUDie, CPUInfo, CPUPackage, CPUTopology, CoreKind, Error, FeatureSet, Frequency, GPUInfo, GPURefreshRate, MemInfo, MemoryUsage, NumaNode, OSInfo, RiscvIsa};
+
+// Where distributions install the full pci.ids database
+const PCI_IDS_PATHS: [&str; 3] = ["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids", "/usr/share/pci.ids"];
+
+// The filesystem root every Linux probe reads /proc, /sys, /etc and /run from. Defaults to
+// "/", but can point at a captured fixture tree or a mounted chroot / container rootfs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SysRoot {
+    root: PathBuf,
+}
+
+impl SysRoot {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        SysRoot { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // Resolves an absolute system path such as "/proc/cpuinfo" below this root
+    pub fn path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        let path = path.as_ref();
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    pub fn read_to_string<P: AsRef<Path>>(&self, path: P) -> io::Result<String> {
+        fs::read_to_string(self.path(path))
+    }
+
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> io::Result<Vec<u8>> {
+        fs::read(self.path(path))
+    }
+
+    pub fn read_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<fs::ReadDir> {
+        fs::read_dir(self.path(path))
+    }
+
+    pub fn read_link<P: AsRef<Path>>(&self, path: P) -> io::Result<PathBuf> {
+        fs::read_link(self.path(path))
+    }
+
+    pub(crate) fn read_trimmed<P: AsRef<Path>>(&self, path: P) -> Option<String> {
+        self.read_to_string(path).ok().map(|content| content.trim().to_string())
+    }
+}
+
+impl Default for SysRoot {
+    fn default() -> Self {
+        SysRoot::new("/")
+    }
+}
+
+impl CPUInfo {
+    #[cfg(target_os = "linux")]
+    pub fn fetch() -> Result<Vec<CPUInfo>, Error> {
+        Self::fetch_from_root(&SysRoot::default())
+    }
+
+    pub fn fetch_from_root(root: &SysRoot) -> Result<Vec<CPUInfo>, Error> {
+        let cpuinfo = root.read_to_string("/proc/cpuinfo")
+            .map_err(|err| Error::io("/proc/cpuinfo", err))?;
+        let processors = parse_cpuinfo(&cpuinfo);
+        if processors.is_empty() {
+            return Err(Error::parse("processor", "no processors listed in /proc/cpuinfo"));
+        }
+
+        // Group the logical processors by the package (socket) they belong to, so that we
+        // return one entry per physical CPU just like Win32_Processor does.
+        let mut packages: BTreeMap<u32, Vec<&HashMap<String, String>>> = BTreeMap::new();
+        for processor in &processors {
+            let cpu = processor.get("processor").and_then(|id| id.parse::<u32>().ok()).unwrap_or(0);
+            let package = root.read_trimmed(format!("/sys/devices/system/cpu/cpu{}/topology/physical_package_id", cpu))
+                .or_else(|| processor.get("physical id").cloned())
+                .and_then(|id| id.parse::<u32>().ok())
+                .unwrap_or(0);
+            packages.entry(package).or_default().push(processor);
+        }
+
+        let kinds = core_kinds(root, &online_cpus(root).unwrap_or_default());
+        Ok(packages.values().map(|processors| cpu_from_package(root, processors, &kinds)).collect())
+    }
+}
+
+impl CPUTopology {
+    #[cfg(target_os = "linux")]
+    pub fn fetch() -> Result<CPUTopology, Error> {
+        Self::fetch_from_root(&SysRoot::default())
+    }
+
+    pub fn fetch_from_root(root: &SysRoot) -> Result<CPUTopology, Error> {
+        let cpus = online_cpus(root)?;
+        let kinds = core_kinds(root, &cpus);
+        let numa_nodes = numa_nodes(root);
+        let node_of = |cpu: u32| numa_nodes.iter().find(|node| node.cpus.contains(&cpu)).map(|node| node.id);
+
+        // Threads of one core share a sibling list, which is the only reliable grouping: core_id
+        // is only unique within a die (and repeats in every cluster on ARM), and die_id within a
+        // package. Kernels before 5.6 have no die_id, and some architectures report -1 for IDs
+        // they don't know.
+        let mut cores: BTreeMap<(u32, u32, u32), (u32, Vec<u32>)> = BTreeMap::new();
+        for cpu in cpus {
+            let id = |name: &str| {
+                root.read_trimmed(format!("/sys/devices/system/cpu/cpu{}/topology/{}", cpu, name))
+                    .and_then(|id| id.parse::<i64>().ok())
+                    .and_then(|id| u32::try_from(id).ok())
+            };
+            let first_sibling = core_siblings(root, cpu).first().copied().unwrap_or(cpu);
+            let key = (id("physical_package_id").unwrap_or(0), id("die_id").unwrap_or(0), first_sibling);
+            cores.entry(key).or_insert_with(|| (id("core_id").unwrap_or(cpu), Vec::new())).1.push(cpu);
+        }
+
+        let mut packages: Vec<CPUPackage> = Vec::new();
+        for ((package, die, _), (core, threads)) in cores {
+            if packages.last().map(|last| last.id) != Some(package) {
+                packages.push(CPUPackage { id: package, dies: Vec::new() });
+            }
+            let dies = &mut packages.last_mut().expect("pushed above").dies;
+            if dies.last().map(|last| last.id) != Some(die) {
+                dies.push(CPUDie { id: die, cores: Vec::new() });
+            }
+            let numa_node = threads.first().and_then(|&cpu| node_of(cpu));
+            let kind = threads.first().and_then(|cpu| kinds.get(cpu).copied());
+            dies.last_mut().expect("pushed above").cores.push(CPUCore { id: core, threads, numa_node, kind });
+        }
+
+        Ok(CPUTopology { packages, numa_nodes })
+    }
+}
+
+impl GPUInfo {
+    #[cfg(target_os = "linux")]
+    pub fn fetch() -> Result<Vec<GPUInfo>, Error> {
+        Self::fetch_from_root(&SysRoot::default())
+    }
+
+    // Every DRM card is one adapter; the connectors (card0-HDMI-A-1, ...) live next to them
+    pub fn fetch_from_root(root: &SysRoot) -> Result<Vec<GPUInfo>, Error> {
+        // No DRM class at all means no DRM driver is loaded, so there are no adapters to report
+        let entries = match root.read_dir("/sys/class/drm") {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(Error::io("/sys/class/drm", err)),
+        };
+        let names: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+
+        let mut cards: Vec<u32> = names
+            .iter()
+            .filter_map(|name| name.strip_prefix("card")?.parse::<u32>().ok())
+            .collect();
+        cards.sort_unstable();
+
+        // Prefer the full database the system ships, the embedded snapshot only knows common GPUs
+        let system_ids = system_pci_ids(root)?;
+        let ids = system_ids.as_ref().unwrap_or_else(|| PciIds::embedded());
+
+        Ok(cards
+            .iter()
+            .enumerate()
+            .map(|(index, card)| gpu_from_card(root, ids, index, *card, &names))
+            .collect())
+    }
+}
+
+// The first pci.ids found in PCI_IDS_PATHS below the root. Only a missing database falls back
+// to the embedded snapshot; one we can't read or parse is reported instead of silently ignored.
+fn system_pci_ids(root: &SysRoot) -> Result<Option<PciIds>, Error> {
+    for path in PCI_IDS_PATHS {
+        let path = root.path(path);
+        if path.exists() {
+            return PciIds::load(&path).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn gpu_from_card(root: &SysRoot, ids: &PciIds, index: usize, card: u32, names: &[String]) -> GPUInfo {
+    let device = format!("/sys/class/drm/card{}/device", card);
+    let uevent: HashMap<String, String> = root.read_to_string(format!("{}/uevent", device))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+    let driver = root.read_link(format!("{}/driver", device))
+        .ok()
+        .and_then(|link| link.file_name().map(|name| name.to_string_lossy().to_string()))
+        .or_else(|| uevent.get("DRIVER").cloned());
+
+    // In-tree drivers such as amdgpu and i915 are versioned with the kernel
+    let driver_version = driver.as_ref()
+        .and_then(|driver| root.read_trimmed(format!("/sys/module/{}/version", driver)))
+        .or_else(|| root.read_trimmed("/proc/sys/kernel/osrelease"))
+        .unwrap_or_default();
+
+    // Only amdgpu reports its dedicated memory through sysfs
+    let memory = root.read_trimmed(format!("{}/mem_info_vram_total", device))
+        .and_then(|bytes| bytes.parse::<u128>().ok())
+        .unwrap_or(0);
+
+    // The preferred (first) mode of every connected output
+    let prefix = format!("card{}-", card);
+    let mut connectors: Vec<&String> = names.iter().filter(|name| name.starts_with(&prefix)).collect();
+    connectors.sort();
+    let video_mode_description = connectors
+        .iter()
+        .filter(|connector| root.read_trimmed(format!("/sys/class/drm/{}/status", connector)).as_deref() == Some("connected"))
+        .filter_map(|connector| {
+            root.read_to_string(format!("/sys/class/drm/{}/modes", connector))
+                .ok()
+                .and_then(|modes| modes.lines().next().map(str::to_string))
+        })
+        .collect();
+
+    // Resolve the PCI IDs to names, keeping the raw "0x10de" style ID when the database doesn't know it
+    let vendor_id = root.read_trimmed(format!("{}/vendor", device));
+    let device_id = root.read_trimmed(format!("{}/device", device));
+    let vendor_code = vendor_id.as_deref().and_then(parse_hex_id);
+    let device_code = device_id.as_deref().and_then(parse_hex_id);
+    let vendor = vendor_code
+        .and_then(|vendor| ids.vendor_name(vendor))
+        .map(str::to_string)
+        .or(vendor_id)
+        .unwrap_or_default();
+    let model = vendor_code
+        .zip(device_code)
+        .and_then(|(vendor, device)| ids.device_name(vendor, device))
+        .map(str::to_string)
+        .or(device_id)
+        .or_else(|| driver.clone())
+        .unwrap_or_default();
+
+    GPUInfo {
+        index: index as u8,
+        vendor,
+        model,
+        memory,
+        device_id: uevent.get("PCI_SLOT_NAME")
+            .cloned()
+            .unwrap_or_else(|| format!("card{}", card)),
+        pnp_device_id: None,
+        refresh_rate: GPURefreshRate { min: 0, max: 0 }, // Not exposed by DRM sysfs
+        status: driver.is_some(),
+        display_drivers_location: driver.into_iter().collect(),
+        driver_version,
+        video_mode_description,
+    }
+}
+
+impl OSInfo {
+    #[cfg(target_os = "linux")]
+    pub fn fetch() -> Result<Vec<OSInfo>, Error> {
+        Self::fetch_from_root(&SysRoot::default())
+    }
+
+    pub fn fetch_from_root(root: &SysRoot) -> Result<Vec<OSInfo>, Error> {
+        let release = root.read_to_string("/etc/os-release")
+            .or_else(|_| root.read_to_string("/usr/lib/os-release"))
+            .map(|content| parse_os_release(&content))
+            .unwrap_or_default();
+        let kernel = |name: &str| root.read_trimmed(format!("/proc/sys/kernel/{}", name));
+
+        let stat = root.read_to_string("/proc/stat").map_err(|err| Error::io("/proc/stat", err))?;
+        let btime = stat
+            .lines()
+            .find_map(|line| line.strip_prefix("btime"))
+            .and_then(|value| value.trim().parse::<i64>().ok())
+            .ok_or_else(|| Error::parse("btime", "missing from /proc/stat"))?;
+        let boot_time = DateTime::from_timestamp(btime, 0)
+            .ok_or_else(|| Error::parse("btime", format!("{} is out of range", btime)))?;
+
+        Ok(vec![OSInfo {
+            name: release.get("PRETTY_NAME")
+                .or_else(|| release.get("NAME"))
+                .cloned()
+                .or_else(|| kernel("ostype"))
+                .unwrap_or_else(|| "Linux".to_string()),
+            short_name: release.get("ID").cloned().unwrap_or_else(|| "linux".to_string()),
+            version: release.get("VERSION_ID")
+                .or_else(|| release.get("VERSION"))
+                .cloned()
+                .or_else(|| kernel("osrelease"))
+                .unwrap_or_default(),
+            os_architecture: machine(root).unwrap_or_default(),
+            status: "OK".to_string(),
+            computer_name: kernel("hostname")
+                .or_else(|| root.read_trimmed("/etc/hostname"))
+                .unwrap_or_default(),
+            boot_time,
+        }])
+    }
+}
+
+impl MemInfo {
+    #[cfg(target_os = "linux")]
+    pub fn fetch() -> Result<Vec<MemInfo>, Error> {
+        Self::fetch_from_root(&SysRoot::default())
+    }
+
+    // One entry per installed DIMM from the SMBIOS tables. Reading those needs root, so without
+    // it we fall back to a single entry covering all of the memory in /proc/meminfo.
+    pub fn fetch_from_root(root: &SysRoot) -> Result<Vec<MemInfo>, Error> {
+        let meminfo = root.read_to_string("/proc/meminfo").map_err(|err| Error::io("/proc/meminfo", err))?;
+        let meminfo = parse_meminfo(&meminfo);
+        let available = meminfo.get("MemAvailable")
+            .or_else(|| meminfo.get("MemFree"))
+            .copied()
+            .unwrap_or(0);
+
+        let modules = smbios(root)
+            .map(|smbios| memory_devices(&smbios))
+            .unwrap_or_default();
+        if !modules.is_empty() {
+            return Ok(modules
+                .into_iter()
+                .enumerate()
+                .map(|(index, module)| MemInfo { index: index as u8, free_memory: available, ..module })
+                .collect());
+        }
+
+        let total = meminfo.get("MemTotal")
+            .copied()
+            .ok_or_else(|| Error::parse("MemTotal", "missing from /proc/meminfo"))?;
+        Ok(vec![MemInfo {
+            index: 0,
+            vendor: String::new(),
+            model: String::new(),
+            name: "System memory".to_string(),
+            serial_number: String::new(),
+            part_number: String::new(),
+            speed_mts: None,
+            total_memory: total,
+            free_memory: available,
+        }])
+    }
+}
+
+impl MemoryUsage {
+    #[cfg(target_os = "linux")]
+    pub fn fetch() -> Result<MemoryUsage, Error> {
+        Self::fetch_from_root(&SysRoot::default())
+    }
+
+    pub fn fetch_from_root(root: &SysRoot) -> Result<MemoryUsage, Error> {
+        let meminfo = root.read_to_string("/proc/meminfo").map_err(|err| Error::io("/proc/meminfo", err))?;
+        let meminfo = parse_meminfo(&meminfo);
+        let field = |key: &str| meminfo.get(key).copied().map(ByteSize::from_bytes);
+
+        let total = field("MemTotal").ok_or_else(|| Error::parse("MemTotal", "missing from /proc/meminfo"))?;
+        let free = field("MemFree");
+        let cached = field("Cached");
+        let buffers = field("Buffers");
+        // MemAvailable only exists since Linux 3.14; before that free + buffers + cache is the usual estimate
+        let available = field("MemAvailable")
+            .unwrap_or_else(|| free.unwrap_or_default() + buffers.unwrap_or_default() + cached.unwrap_or_default());
+        let swap_total = field("SwapTotal").unwrap_or_default();
+        let swap_used = ByteSize::from_bytes(swap_total.bytes().saturating_sub(field("SwapFree").unwrap_or_default().bytes()));
+
+        Ok(MemoryUsage {
+            total,
+            available,
+            free,
+            cached,
+            buffers,
+            swap_total,
+            swap_used,
+            committed: field("Committed_AS"),
+            commit_limit: field("CommitLimit"),
+        })
+    }
+}
+
+fn cpu_from_package(root: &SysRoot, processors: &[&HashMap<String, String>], kinds: &BTreeMap<u32, CoreKind>) -> CPUInfo {
+    let id = |processor: &HashMap<String, String>| processor.get("processor").and_then(|id| id.parse::<u32>().ok());
+    let cpus: Vec<u32> = processors.iter().filter_map(|processor| id(processor)).collect();
+    // A hybrid package is described by its performance cores, e.g. an RK3588 is a Cortex-A76
+    // part even though the kernel lists its Cortex-A55 cores first
+    let first = processors
+        .iter()
+        .copied()
+        .find(|processor| id(processor).and_then(|cpu| kinds.get(&cpu)) == Some(&CoreKind::Performance))
+        .unwrap_or(processors[0]);
+    let field = |key: &str| first.get(key).cloned().unwrap_or_default();
+
+    // ARM only lists the MIDR implementer and part, and RISC-V its JEDEC vendor ID and core
+    // ("uarch", e.g. "sifive,u74-mc"), so we name those processors ourselves
+    let implementer = first.get("CPU implementer").and_then(|id| parse_hex_id(id)).and_then(|id| u8::try_from(id).ok());
+    let part = first.get("CPU part").and_then(|id| parse_hex_id(id));
+    let uarch = first.get("uarch").and_then(|uarch| uarch.split_once(','));
+    let vendor = first.get("vendor_id")
+        .cloned()
+        .or_else(|| implementer.map(|id| microarch::arm_implementer(id).map(String::from).unwrap_or_else(|| format!("0x{:02x}", id))))
+        .or_else(|| {
+            let id = first.get("mvendorid")?;
+            microarch::riscv_vendor(u64::from_str_radix(id.trim_start_matches("0x"), 16).ok()?).map(String::from)
+        })
+        .or_else(|| uarch.map(|(vendor, _)| vendor.to_string()))
+        .unwrap_or_default();
+    let name = implementer
+        .zip(part)
+        .and_then(|(implementer, part)| microarch::arm(implementer, part))
+        .map(|part| format!("{} {}", vendor, part))
+        .or_else(|| first.get("model name").cloned())
+        .or_else(|| uarch.map(|(_, core)| format!("{} {}", vendor, core.to_ascii_uppercase())))
+        .or_else(|| first.get("Hardware").cloned())
+        .unwrap_or_default();
+    let architecture = architecture(root);
+
+    // Mirror the Win32_Processor description, e.g. "Intel64 Family 6 Model 158 Stepping 10"
+    let model = if first.contains_key("cpu family") {
+        let prefix = match (vendor.as_str(), architecture) {
+            ("GenuineIntel", CPUArchitecture::X64) => "Intel64",
+            ("AuthenticAMD", CPUArchitecture::X64) => "AMD64",
+            _ => "x86",
+        };
+        format!("{} Family {} Model {} Stepping {}", prefix, field("cpu family"), field("model"), field("stepping"))
+    } else {
+        name.clone()
+    };
+
+    // Prefer the current frequency reported by cpufreq, falling back to the value in /proc/cpuinfo
+    let frequency = id(first)
+        .and_then(|cpu| root.read_trimmed(format!("/sys/devices/system/cpu/cpu{}/cpufreq/scaling_cur_freq", cpu)))
+        .and_then(|khz| khz.parse::<u64>().ok())
+        .map(Frequency::from_khz)
+        .or_else(|| first.get("cpu MHz").and_then(|mhz| mhz.parse::<f64>().ok()).map(|mhz| Frequency::from_hz((mhz * 1_000_000.0).round() as u64)))
+        .unwrap_or_default();
+
+    let cores = first.get("cpu cores")
+        .and_then(|cores| cores.parse::<u32>().ok())
+        .unwrap_or_else(|| count_cores(root, &cpus));
+
+    let virtualisation = first.get("flags")
+        .map(|flags| flags.split_whitespace().any(|flag| flag == "vmx" || flag == "svm"))
+        .unwrap_or(false);
+
+    // x86 lists its features under "flags", ARM under "Features". The auxiliary vector adds
+    // what the kernel advertises to userspace.
+    let mut features = first.get("flags")
+        .or_else(|| first.get("Features"))
+        .map(|flags| FeatureSet::from_cpuinfo_flags(flags))
+        .unwrap_or_default();
+    if let Some((hwcap, hwcap2)) = hwcaps(root, architecture) {
+        features.extend_from(&FeatureSet::from_hwcap(architecture, hwcap, hwcap2));
+    }
+
+    let mut cpu = CPUInfo {
+        vendor,
+        model,
+        name,
+        frequency,
+        architecture,
+        cores,
+        logical_cores: processors.len() as u32,
+        cache_size: cache_size(root, &cpus),
+        virtualisation,
+        features,
+        x86_64_level: None,
+        microarchitecture: microarchitecture(first),
+        performance_cores: None,
+        efficiency_cores: None,
+        riscv_isa: first.get("isa").and_then(|isa| RiscvIsa::parse(isa).ok()),
+        mmu: first.get("mmu").cloned(),
+    };
+    if !kinds.is_empty() {
+        // Count each physical core once, by the first of its hardware threads
+        let primary = |cpu: u32| core_siblings(root, cpu).first().is_none_or(|&first| first == cpu);
+        let count = |kind: CoreKind| cpus.iter().filter(|&&cpu| kinds.get(&cpu) == Some(&kind) && primary(cpu)).count() as u32;
+        cpu.performance_cores = Some(count(CoreKind::Performance));
+        cpu.efficiency_cores = Some(count(CoreKind::Efficiency));
+    }
+    // CPUID covers what the kernel leaves out of cpuinfo (e.g. caches under some hypervisors),
+    // but it only describes the machine we're running on, not a fixture root. The cpuinfo
+    // flags stay authoritative; CPUID features are only used when there are none.
+    if root.root() == Path::new("/") {
+        if let Some(cpuid) = Cpuid::read() {
+            cpu.fill_from_cpuid(&cpuid);
+        }
+    }
+    cpu.x86_64_level = cpu.detect_x86_64_level();
+    cpu
+}
+
+// x86 lists a decimal family, model and stepping, ARM the hex MIDR implementer and part, and
+// RISC-V the core itself
+fn microarchitecture(processor: &HashMap<String, String>) -> Option<String> {
+    let decimal = |key: &str| processor.get(key).and_then(|value| value.parse::<u32>().ok());
+    let name = match (processor.get("vendor_id"), processor.get("CPU implementer"), processor.get("CPU part")) {
+        (Some(vendor), _, _) => microarch::x86(vendor, decimal("cpu family")?, decimal("model")?, decimal("stepping").unwrap_or(0)),
+        (None, Some(implementer), Some(part)) => microarch::arm(u8::try_from(parse_hex_id(implementer)?).ok()?, parse_hex_id(part)?),
+        _ => {
+            let (_, core) = processor.get("uarch")?.split_once(',')?;
+            return Some(core.to_ascii_uppercase());
+        }
+    };
+    name.map(String::from)
+}
+
+// Splits /proc/cpuinfo into one key/value map per logical processor
+fn parse_cpuinfo(content: &str) -> Vec<HashMap<String, String>> {
+    content
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect::<HashMap<String, String>>()
+        })
+        .filter(|block| block.contains_key("processor"))
+        .collect()
+}
+
+// core_id is only unique within a cluster on device-tree arm64, so cores are told apart by the
+// set of hardware threads they run instead
+fn count_cores(root: &SysRoot, cpus: &[u32]) -> u32 {
+    let cores: BTreeSet<Vec<u32>> = cpus.iter().map(|&cpu| core_siblings(root, cpu)).collect();
+    cores.len() as u32
+}
+
+// Every logical CPU on the same physical core as `cpu`, including itself. core_cpus_list is
+// the newer name for thread_siblings_list (Linux 5.7+); without either the CPU is its own core.
+fn core_siblings(root: &SysRoot, cpu: u32) -> Vec<u32> {
+    let dir = format!("/sys/devices/system/cpu/cpu{}/topology", cpu);
+    root.read_trimmed(format!("{}/core_cpus_list", dir))
+        .or_else(|| root.read_trimmed(format!("{}/thread_siblings_list", dir)))
+        .and_then(|list| parse_cpu_list(&list))
+        .filter(|siblings| !siblings.is_empty())
+        .unwrap_or_else(|| vec![cpu])
+}
+
+// Sums the caches of every level across the package, counting caches shared between
+// several logical processors only once, like the per-package totals of Win32_Processor.
+fn cache_size(root: &SysRoot, cpus: &[u32]) -> CPUCacheSize {
+    let mut seen = BTreeSet::new();
+    let mut levels: BTreeMap<u32, ByteSize> = BTreeMap::new();
+
+    for cpu in cpus {
+        let dir = format!("/sys/devices/system/cpu/cpu{}/cache", cpu);
+        let Ok(entries) = root.read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with("index") {
+                continue;
+            }
+            let path = format!("{}/{}", dir, name);
+            let (Some(level), Some(kind), Some(size)) = (
+                root.read_trimmed(format!("{}/level", path)).and_then(|level| level.parse::<u32>().ok()),
+                root.read_trimmed(format!("{}/type", path)),
+                root.read_trimmed(format!("{}/size", path)).and_then(|size| parse_cache_size(&size)),
+            ) else { continue };
+            let shared = root.read_trimmed(format!("{}/shared_cpu_list", path)).unwrap_or_else(|| cpu.to_string());
+            if seen.insert((level, kind, shared)) {
+                *levels.entry(level).or_default() += size;
+            }
+        }
+    }
+
+    CPUCacheSize {
+        l1: levels.get(&1).copied(),
+        l2: levels.get(&2).copied(),
+        l3: levels.get(&3).copied(),
+    }
+}
+
+// Parses sysfs cache sizes such as "48K" or "32M"
+fn parse_cache_size(size: &str) -> Option<ByteSize> {
+    let (value, multiplier) = match size.chars().last()? {
+        'K' => (&size[..size.len() - 1], 1024),
+        'M' => (&size[..size.len() - 1], 1024 * 1024),
+        'G' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    value.parse::<u64>().ok().map(|value| ByteSize::from_bytes(value * multiplier))
+}
+
+// The logical CPUs that are online, falling back to every cpuN directory when the online mask
+// is missing
+fn online_cpus(root: &SysRoot) -> Result<Vec<u32>, Error> {
+    if let Some(cpus) = root.read_trimmed("/sys/devices/system/cpu/online").and_then(|list| parse_cpu_list(&list)) {
+        return Ok(cpus);
+    }
+    let dir = "/sys/devices/system/cpu";
+    let mut cpus: Vec<u32> = root.read_dir(dir)
+        .map_err(|err| Error::io(dir, err))?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_prefix("cpu")?.parse().ok())
+        .collect();
+    cpus.sort_unstable();
+    Ok(cpus)
+}
+
+// Machines without NUMA support have no node directory at all, which we report as no nodes
+fn numa_nodes(root: &SysRoot) -> Vec<NumaNode> {
+    let dir = "/sys/devices/system/node";
+    let Ok(entries) = root.read_dir(dir) else { return Vec::new() };
+    let mut nodes: Vec<NumaNode> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_prefix("node")?.parse::<u32>().ok())
+        .map(|id| {
+            let path = format!("{}/node{}", dir, id);
+            // Lines look like "Node 0 MemTotal:       16318924 kB"
+            let memory = root.read_to_string(format!("{}/meminfo", path))
+                .ok()
+                .and_then(|meminfo| {
+                    let prefix = format!("Node {} ", id);
+                    let lines: String = meminfo.lines().filter_map(|line| line.strip_prefix(&prefix)).collect::<Vec<_>>().join("\n");
+                    parse_meminfo(&lines).get("MemTotal").copied()
+                })
+                .map(ByteSize::from_bytes);
+            NumaNode {
+                id,
+                cpus: root.read_trimmed(format!("{}/cpulist", path)).and_then(|list| parse_cpu_list(&list)).unwrap_or_default(),
+                memory,
+            }
+        })
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    nodes
+}
+
+// The kind of every CPU on hybrid processors, empty when all cores are alike. Intel hybrid
+// parts get one PMU per core type (Linux 5.13+), and ARM reports each core's relative
+// performance in cpu_capacity. Failing both, we compare the MIDR of every core, or ask CPUID
+// when we're looking at the machine we run on.
+fn core_kinds(root: &SysRoot, cpus: &[u32]) -> BTreeMap<u32, CoreKind> {
+    let pmu = |name: &str| root.read_trimmed(format!("/sys/devices/{}/cpus", name)).and_then(|list| parse_cpu_list(&list));
+    let per_cpu = |name: &str| -> Option<Vec<String>> {
+        cpus.iter().map(|cpu| root.read_trimmed(format!("/sys/devices/system/cpu/cpu{}/{}", cpu, name))).collect()
+    };
+
+    let kinds: BTreeMap<u32, CoreKind> = if let (Some(performance), Some(efficiency)) = (pmu("cpu_core"), pmu("cpu_atom")) {
+        cpus.iter()
+            .filter_map(|&cpu| match (performance.contains(&cpu), efficiency.contains(&cpu)) {
+                (true, _) => Some((cpu, CoreKind::Performance)),
+                (_, true) => Some((cpu, CoreKind::Efficiency)),
+                _ => None,
+            })
+            .collect()
+    } else if let Some(capacities) = per_cpu("cpu_capacity").and_then(|values| values.iter().map(|value| value.parse::<u32>().ok()).collect::<Option<Vec<_>>>()) {
+        // The biggest cores are 1024 and the rest scaled to match
+        let smallest = capacities.iter().min().copied().unwrap_or(0);
+        cpus.iter()
+            .zip(&capacities)
+            .map(|(&cpu, &capacity)| (cpu, if capacity == smallest { CoreKind::Efficiency } else { CoreKind::Performance }))
+            .collect()
+    } else if let Some(midrs) = per_cpu("regs/identification/midr_el1") {
+        // MIDR_EL1: implementer in bits 31:24, part number in bits 15:4
+        cpus.iter()
+            .zip(&midrs)
+            .filter_map(|(&cpu, midr)| {
+                let midr = u64::from_str_radix(midr.trim_start_matches("0x"), 16).ok()?;
+                let efficiency = microarch::arm_is_efficiency_core((midr >> 24) as u8, ((midr >> 4) & 0xFFF) as u16);
+                Some((cpu, if efficiency { CoreKind::Efficiency } else { CoreKind::Performance }))
+            })
+            .collect()
+    } else if root.root() == Path::new("/") {
+        cpuid_core_kinds(cpus)
+    } else {
+        BTreeMap::new()
+    };
+
+    let distinct: BTreeSet<CoreKind> = kinds.values().copied().collect();
+    if distinct.len() > 1 { kinds } else { BTreeMap::new() }
+}
+
+// CPUID leaf 0x1A only describes the core the instruction runs on, so a short-lived helper
+// thread moves itself onto each CPU in turn. The caller's own affinity is never touched.
+#[cfg(target_os = "linux")]
+fn cpuid_core_kinds(cpus: &[u32]) -> BTreeMap<u32, CoreKind> {
+    if Cpuid::read().and_then(|cpuid| cpuid.core_kind).is_none() {
+        return BTreeMap::new();
+    }
+    // cpu_set_t only has room for CPU_SETSIZE CPUs, and CPU_SET panics beyond that
+    let cpus: Vec<u32> = cpus.iter().copied().filter(|&cpu| cpu < libc::CPU_SETSIZE as u32).collect();
+    let probe = std::thread::spawn(move || {
+        let mut kinds = BTreeMap::new();
+        let size = std::mem::size_of::<libc::cpu_set_t>();
+        for cpu in cpus {
+            let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+            unsafe { libc::CPU_SET(cpu as usize, &mut set) };
+            if unsafe { libc::sched_setaffinity(0, size, &set) } != 0 {
+                continue;
+            }
+            if let Some(kind) = Cpuid::read().and_then(|cpuid| cpuid.core_kind) {
+                kinds.insert(cpu, kind);
+            }
+        }
+        kinds
+    });
+    probe.join().unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpuid_core_kinds(_cpus: &[u32]) -> BTreeMap<u32, CoreKind> {
+    BTreeMap::new()
+}
+
+// Parses the kernel's CPU list format, e.g. "0-3,8-11" or "" for an empty set
+pub(crate) fn parse_cpu_list(list: &str) -> Option<Vec<u32>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => cpus.extend(start.trim().parse::<u32>().ok()?..=end.trim().parse::<u32>().ok()?),
+            None => cpus.push(range.trim().parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+// AT_HWCAP and AT_HWCAP2 from our own auxiliary vector, a list of native-endian
+// (type, value) word pairs ending with AT_NULL. The words are as wide as the root's
+// architecture, which isn't necessarily ours.
+fn hwcaps(root: &SysRoot, architecture: CPUArchitecture) -> Option<(u64, u64)> {
+    const AT_NULL: u64 = 0;
+    const AT_HWCAP: u64 = 16;
+    const AT_HWCAP2: u64 = 26;
+
+    let word = match architecture {
+        CPUArchitecture::Neutral | CPUArchitecture::Unknown => return None,
+        CPUArchitecture::X86 | CPUArchitecture::Arm | CPUArchitecture::RiscV32 | CPUArchitecture::PowerPC | CPUArchitecture::Mips => 4,
+        _ => 8,
+    };
+    let auxv = root.read("/proc/self/auxv").ok()?;
+    let value = |bytes: &[u8]| -> u64 {
+        match word {
+            4 => u32::from_ne_bytes(bytes.try_into().unwrap_or_default()) as u64,
+            _ => u64::from_ne_bytes(bytes.try_into().unwrap_or_default()),
+        }
+    };
+
+    let (mut hwcap, mut hwcap2) = (None, 0);
+    for pair in auxv.chunks_exact(word * 2) {
+        match value(&pair[..word]) {
+            AT_NULL => break,
+            AT_HWCAP => hwcap = Some(value(&pair[word..])),
+            AT_HWCAP2 => hwcap2 = value(&pair[word..]),
+            _ => {}
+        }
+    }
+    Some((hwcap?, hwcap2))
+}
+
+// Parses /proc/meminfo into bytes, e.g. "MemTotal:       16318924 kB"
+pub(crate) fn parse_meminfo(content: &str) -> HashMap<String, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut parts = value.split_whitespace();
+            let amount = parts.next()?.parse::<u64>().ok()?;
+            let amount = match parts.next() {
+                Some("kB") => amount * 1024,
+                _ => amount,
+            };
+            Some((key.trim().to_string(), amount))
+        })
+        .collect()
+}
+
+// Reads /sys/firmware/dmi/tables, which is only readable by root
+fn smbios(root: &SysRoot) -> Result<Smbios, Error> {
+    let table_path = "/sys/firmware/dmi/tables/DMI";
+    let table = root.read(table_path).map_err(|err| Error::io(table_path, err))?;
+    match root.read("/sys/firmware/dmi/tables/smbios_entry_point") {
+        Ok(entry_point) => Smbios::parse(&entry_point, &table),
+        Err(_) => Smbios::parse_table(&table, None),
+    }
+}
+
+// The populated Memory Devices (type 17), leaving out empty slots and modules of unknown size
+fn memory_devices(smbios: &Smbios) -> Vec<MemInfo> {
+    smbios
+        .memory_devices()
+        .into_iter()
+        .filter_map(|device| {
+            Some(MemInfo {
+                index: 0,
+                total_memory: device.size?.bytes(),
+                vendor: device.manufacturer,
+                model: String::new(),
+                name: device.device_locator,
+                serial_number: device.serial_number,
+                part_number: device.part_number,
+                speed_mts: device.configured_speed_mts.or(device.speed_mts),
+                free_memory: 0,
+            })
+        })
+        .collect()
+}
+
+// Parses the KEY=value lines of os-release(5), unquoting shell-style values
+fn parse_os_release(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim();
+            let unquoted = value
+                .strip_prefix('"').and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            let mut unescaped = String::with_capacity(unquoted.len());
+            let mut chars = unquoted.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => unescaped.extend(chars.next()),
+                    c => unescaped.push(c),
+                }
+            }
+            (key.trim().to_string(), unescaped)
+        })
+        .collect()
+}
+
+// The uname(2) machine string, e.g. "x86_64". Newer kernels also expose it in procfs, which is
+// what we use for fixture roots; the syscall only describes the host we're running on.
+fn machine(root: &SysRoot) -> Option<String> {
+    root.read_trimmed("/proc/sys/kernel/arch")
+        .or_else(|| if root.root() == Path::new("/") { uname_machine() } else { None })
+}
+
+#[cfg(unix)]
+fn uname_machine() -> Option<String> {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return None;
+    }
+    let machine = unsafe { std::ffi::CStr::from_ptr(name.machine.as_ptr()) };
+    Some(machine.to_string_lossy().into_owned())
+}
+
+#[cfg(not(unix))]
+fn uname_machine() -> Option<String> {
+    None
+}
+
+// The kernel's idea of the machine, which is what the processor is even when running 32-bit
+// userspace on a 64-bit kernel. What we were compiled for only says something about the host.
+fn architecture(root: &SysRoot) -> CPUArchitecture {
+    match machine(root) {
+        Some(machine) => CPUArchitecture::from_uname(&machine),
+        None if root.root() == Path::new("/") => CPUArchitecture::current(),
+        None => CPUArchitecture::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Feature, X86_64Level};
+
+    fn fixture(name: &str) -> SysRoot {
+        SysRoot::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/linux").join(name))
+    }
+
+    // A synthetic desktop with a 2-core, 4-thread Core i3-7100: SMT siblings (0,2) and (1,3),
+    // 32K L1d, 32K L1i and 256K L2 per core, and a shared 3M L3
+    #[test]
+    fn cpu_from_desktop_fixture() {
+        let cpus = CPUInfo::fetch_from_root(&fixture("desktop")).unwrap();
+        assert_eq!(cpus.len(), 1);
+        let cpu = &cpus[0];
+        assert_eq!(cpu.vendor, "GenuineIntel");
+        assert_eq!(cpu.name, "Intel(R) Core(TM) i3-7100 CPU @ 3.90GHz");
+        assert_eq!(cpu.model, "Intel64 Family 6 Model 158 Stepping 9");
+        assert_eq!(cpu.architecture, CPUArchitecture::X64);
+        assert_eq!(cpu.frequency, Frequency::from_mhz(3900));
+        assert_eq!((cpu.cores, cpu.logical_cores), (2, 4));
+        assert_eq!(cpu.cache_size.l1, Some(ByteSize::from_kib(2 * (32 + 32))));
+        assert_eq!(cpu.cache_size.l2, Some(ByteSize::from_kib(2 * 256)));
+        assert_eq!(cpu.cache_size.l3, Some(ByteSize::from_kib(3072)));
+        assert!(cpu.virtualisation);
+        assert!(cpu.features.contains_all(&[Feature::Sse3, Feature::Avx2, Feature::Lzcnt]));
+        assert_eq!(cpu.microarchitecture.as_deref(), Some("Kaby Lake"));
+        assert_eq!(cpu.x86_64_level, Some(X86_64Level::V3));
+        assert_eq!(cpu.performance_cores, None);
+    }
+
+    #[test]
+    fn topology_from_desktop_fixture() {
+        let topology = CPUTopology::fetch_from_root(&fixture("desktop")).unwrap();
+        assert_eq!(topology.packages.len(), 1);
+        let threads: Vec<&[u32]> = topology.cores().map(|core| core.threads.as_slice()).collect();
+        assert_eq!(threads, [[0, 2], [1, 3]]);
+        assert_eq!(topology.primary_threads(), [0, 1]);
+        assert!(topology.numa_nodes.is_empty());
+        assert!(!topology.is_hybrid());
+    }
+
+    // RK3588: Cortex-A55 cluster 0 and two Cortex-A76 clusters, with core_id restarting in each
+    #[test]
+    fn topology_from_big_little_fixture() {
+        let topology = CPUTopology::fetch_from_root(&fixture("big-little")).unwrap();
+        let cores: Vec<(u32, &[u32], Option<CoreKind>)> =
+            topology.cores().map(|core| (core.id, core.threads.as_slice(), core.kind)).collect();
+        assert_eq!(cores.len(), 8);
+        assert_eq!(cores[3], (3, &[3][..], Some(CoreKind::Efficiency)));
+        assert_eq!(cores[4], (0, &[4][..], Some(CoreKind::Performance)));
+        assert_eq!(cores[6], (0, &[6][..], Some(CoreKind::Performance)));
+        assert_eq!(topology.primary_threads(), [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(topology.is_hybrid());
+    }
+
+    #[test]
+    fn cpu_from_big_little_fixture() {
+        let cpus = CPUInfo::fetch_from_root(&fixture("big-little")).unwrap();
+        assert_eq!(cpus.len(), 1);
+        let cpu = &cpus[0];
+        assert_eq!(cpu.architecture, CPUArchitecture::Arm64);
+        assert_eq!((cpu.cores, cpu.logical_cores), (8, 8));
+        assert_eq!((cpu.performance_cores, cpu.efficiency_cores), (Some(4), Some(4)));
+        // Named after the Cortex-A76 performance cores, not the Cortex-A55s listed first
+        assert_eq!(cpu.vendor, "ARM");
+        assert_eq!(cpu.name, "ARM Cortex-A76");
+        assert_eq!(cpu.microarchitecture.as_deref(), Some("Cortex-A76"));
+        assert_eq!(cpu.frequency, Frequency::from_mhz(2400));
+    }
+
+    // The fixture's /proc/self/auxv is a 64-bit little-endian vector whatever we're running on
+    #[test]
+    fn hwcaps_from_big_little_fixture() {
+        let root = fixture("big-little");
+        assert_eq!(hwcaps(&root, CPUArchitecture::Arm64), Some((0x0011_9FFF, 0)));
+        assert_eq!(hwcaps(&root, CPUArchitecture::Unknown), None);
+        assert_eq!(hwcaps(&fixture("desktop"), CPUArchitecture::X64), None);
+
+        let features = FeatureSet::from_hwcap(CPUArchitecture::Arm64, 0x0011_9FFF, 0);
+        for feature in [Feature::Fp, Feature::Neon, Feature::Aes, Feature::Atomics, Feature::Asimdhp, Feature::DotProd] {
+            assert!(features.contains(feature), "{:?}", feature);
+        }
+        assert!(!features.contains(Feature::Sve));
+        assert!(FeatureSet::from_hwcap(CPUArchitecture::X64, 0x0011_9FFF, 0).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn core_kind_probe_leaves_the_caller_affinity_alone() {
+        let affinity = || {
+            let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+            unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) };
+            (0..libc::CPU_SETSIZE as usize).filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) }).collect::<Vec<usize>>()
+        };
+        let before = affinity();
+        let kinds = cpuid_core_kinds(&[0, libc::CPU_SETSIZE as u32, 4096]);
+        assert!(kinds.keys().all(|&cpu| cpu < libc::CPU_SETSIZE as u32));
+        assert_eq!(affinity(), before);
+    }
+
+    #[test]
+    fn architecture_comes_from_the_root() {
+        assert_eq!(architecture(&fixture("desktop")), CPUArchitecture::X64);
+        assert_eq!(architecture(&fixture("big-little")), CPUArchitecture::Arm64);
+        assert_eq!(architecture(&fixture("does-not-exist")), CPUArchitecture::Unknown);
+    }
+
+    #[test]
+    fn machine_names() {
+        let cases = [
+            ("x86_64", CPUArchitecture::X64),
+            ("i686", CPUArchitecture::X86),
+            ("aarch64", CPUArchitecture::Arm64),
+            ("armv8l", CPUArchitecture::Arm),
+            ("armv7l", CPUArchitecture::Arm),
+            ("riscv64", CPUArchitecture::RiscV64),
+            ("ppc64le", CPUArchitecture::PowerPC64),
+            ("s390x", CPUArchitecture::S390x),
+            ("vax", CPUArchitecture::Unknown),
+        ];
+        for (machine, architecture) in cases {
+            assert_eq!(CPUArchitecture::from_uname(machine), architecture, "{}", machine);
+        }
+    }
+
+    #[test]
+    fn gpu_from_desktop_fixture() {
+        let gpus = GPUInfo::fetch_from_root(&fixture("desktop")).unwrap();
+        assert_eq!(gpus.len(), 1);
+        let gpu = &gpus[0];
+        assert_eq!(gpu.vendor, "Advanced Micro Devices, Inc. [AMD/ATI]");
+        assert_eq!(gpu.model, "Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]");
+        assert_eq!(gpu.device_id, "0000:03:00.0");
+        assert_eq!(gpu.memory, 17_163_091_968);
+        assert_eq!(gpu.display_drivers_location, ["amdgpu"]);
+        assert_eq!(gpu.driver_version, "6.8.0-45-generic");
+        assert_eq!(gpu.video_mode_description, ["2560x1440"]);
+        assert!(gpu.status);
+    }
+
+    #[test]
+    fn broken_pci_ids_are_reported() {
+        assert_eq!(system_pci_ids(&fixture("desktop")), Ok(None));
+
+        let root = std::env::temp_dir().join(format!("hysterical-pci-ids-{}", std::process::id()));
+        fs::create_dir_all(root.join("usr/share/misc")).unwrap();
+        fs::write(root.join("usr/share/misc/pci.ids"), "10de  NVIDIA Corporation\n\t\t\t2684  AD102\n").unwrap();
+        let result = system_pci_ids(&SysRoot::new(&root));
+        fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(result, Err(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn os_from_desktop_fixture() {
+        let os = OSInfo::fetch_from_root(&fixture("desktop")).unwrap();
+        let os = &os[0];
+        assert_eq!(os.name, "Ubuntu 24.04.1 LTS");
+        assert_eq!(os.short_name, "ubuntu");
+        assert_eq!(os.version, "24.04");
+        assert_eq!(os.os_architecture, "x86_64");
+        assert_eq!(os.computer_name, "workstation");
+        assert_eq!(os.boot_time().to_rfc3339(), "2024-06-01T10:00:00+00:00");
+    }
+
+    // The SMBIOS tables of tests/fixtures/smbios (a B550-A PRO with 16G in DIMM_A1, 32G in
+    // DIMM_A2 and two empty slots) under /sys/firmware/dmi/tables
+    #[test]
+    fn memory_from_smbios_fixture() {
+        let modules = MemInfo::fetch_from_root(&fixture("ryzen")).unwrap();
+        let slots: Vec<(u8, &str, u64, Option<u32>)> = modules
+            .iter()
+            .map(|module| (module.index, module.name.as_str(), module.total_memory, module.speed_mts))
+            .collect();
+        assert_eq!(slots, [(0, "DIMM_A1", 16 << 30, Some(3600)), (1, "DIMM_A2", 32 << 30, Some(3600))]);
+        assert_eq!(modules.iter().map(|module| module.total_memory).sum::<u64>(), 48 << 30);
+        assert!(modules.iter().all(|module| module.vendor == "Kingston" && module.free_memory == 41_876_520 * 1024));
+        assert_eq!(modules[1].part_number, "KF3600C18D4/32GX");
+    }
+
+    #[test]
+    fn memory_falls_back_to_meminfo_without_smbios() {
+        let modules = MemInfo::fetch_from_root(&fixture("desktop")).unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "System memory");
+        assert_eq!(modules[0].total_memory, 16_318_924 * 1024);
+        assert_eq!(modules[0].free_memory, 9_634_704 * 1024);
+    }
+
+    #[test]
+    fn memory_usage_from_desktop_fixture() {
+        let usage = MemoryUsage::fetch_from_root(&fixture("desktop")).unwrap();
+        assert_eq!(usage.total, ByteSize::from_kib(16_318_924));
+        assert_eq!(usage.available, ByteSize::from_kib(9_634_704));
+        assert_eq!(usage.free, Some(ByteSize::from_kib(1_887_316)));
+        assert_eq!(usage.cached, Some(ByteSize::from_kib(7_455_216)));
+        assert_eq!(usage.buffers, Some(ByteSize::from_kib(512_876)));
+        assert_eq!(usage.swap_used, ByteSize::from_kib(2_097_148 - 1_572_860));
+        assert_eq!(usage.committed, Some(ByteSize::from_kib(12_402_848)));
+    }
+
+    #[test]
+    fn missing_root_is_source_unavailable() {
+        let root = fixture("does-not-exist");
+        assert!(matches!(CPUInfo::fetch_from_root(&root), Err(Error::SourceUnavailable { .. })));
+        assert!(matches!(MemoryUsage::fetch_from_root(&root), Err(Error::SourceUnavailable { .. })));
+        assert!(GPUInfo::fetch_from_root(&root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn cpu_lists() {
+        assert_eq!(parse_cpu_list("0-3,8-9,12"), Some(vec![0, 1, 2, 3, 8, 9, 12]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("0-x"), None);
+    }
+}
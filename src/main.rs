@@ -1,6 +1,161 @@
-fn main() {
-    //hysterical::utils::testing::fetch_query_based("Win32_PhysicalMemory");
-    
-    let thing = hysterical::MemInfo::fetch();
-    println!("{:#?}", thing)
+use std::process::ExitCode;
+use hysterical::diff::SnapshotDiff;
+use hysterical::{CPUInfo, Error, GPUInfo, MemInfo, OSInfo, SystemSnapshot};
+use serde::Serialize;
+use serde_json::Value;
+
+mod output;
+
+use output::Format;
+
+const USAGE: &str = "\
+hysterical - hardware and software information
+
+Usage:
+  hysterical [COMMAND] [OPTIONS]
+  hysterical diff <before.json> <after.json>
+
+Commands:
+  all        Everything in one snapshot (default)
+  cpu        Processors
+  gpu        Graphics adapters
+  mem        Memory modules
+  os         Operating system
+  diff       Compare two snapshots written with `all --format json`
+
+Options:
+  -f, --format <FORMAT>   table, json, yaml or toml (default: table)
+      --fields <FIELDS>   Comma-separated fields to print, e.g. name,cache_size.l2
+  -h, --help              Print this help
+
+Exit codes:
+  0  Success (for diff: no changes)
+  1  A collector failed (for diff: something changed)
+  2  Invalid usage or unreadable input
+";
+
+struct Options {
+    command: String,
+    arguments: Vec<String>,
+    format: Format,
+    fields: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if options.command != "diff" && !options.arguments.is_empty() {
+        eprintln!("error: unexpected argument '{}'\n\n{}", options.arguments[0], USAGE);
+        return ExitCode::from(2);
+    }
+
+    match options.command.as_str() {
+        "all" => {
+            let snapshot = SystemSnapshot::collect();
+            for failure in &snapshot.errors {
+                eprintln!("error: {}: {}", failure.component, failure.error);
+            }
+            print(&options, "snapshot", &snapshot);
+            if snapshot.is_complete() { ExitCode::SUCCESS } else { ExitCode::from(1) }
+        }
+        "cpu" => print_component(&options, "cpu", CPUInfo::fetch()),
+        "gpu" => print_component(&options, "gpu", GPUInfo::fetch()),
+        "mem" => print_component(&options, "mem", MemInfo::fetch()),
+        "os" => print_component(&options, "os", OSInfo::fetch()),
+        "diff" => diff(&options),
+        other => {
+            eprintln!("error: unknown command '{}'\n\n{}", other, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+// Returns None when help was requested
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+    let mut positional = Vec::new();
+    let mut format = Format::Table;
+    let mut fields = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| format!("{} needs a value", name))
+        };
+        match flag.as_str() {
+            "-h" | "--help" | "help" => return Ok(None),
+            "-f" | "--format" => format = value("--format")?.parse()?,
+            "--fields" => {
+                fields = value("--fields")?
+                    .split(',')
+                    .map(|field| field.trim().to_string())
+                    .filter(|field| !field.is_empty())
+                    .collect()
+            }
+            _ if flag.starts_with('-') => return Err(format!("unknown option '{}'", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = positional.next().unwrap_or_else(|| String::from("all"));
+    Ok(Some(Options { command, arguments: positional.collect(), format, fields }))
+}
+
+fn print_component<T: Serialize>(options: &Options, name: &str, result: Result<Vec<T>, Error>) -> ExitCode {
+    match result {
+        Ok(items) => {
+            print(options, name, &items);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}: {}", name, err);
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn print<T: Serialize>(options: &Options, name: &str, data: &T) {
+    let value = serde_json::to_value(data).unwrap_or(Value::Null);
+    let value = output::select(value, &options.fields);
+    print!("{}", output::render(options.format, name, &value));
+}
+
+// hysterical diff a.json b.json
+// Exits with 0 when nothing changed, 1 when something did and 2 when the snapshots can't be read
+fn diff(options: &Options) -> ExitCode {
+    let [before, after] = options.arguments.as_slice() else {
+        eprintln!("usage: hysterical diff <before.json> <after.json>");
+        return ExitCode::from(2);
+    };
+    let snapshots = SystemSnapshot::from_path(before).and_then(|before| Ok((before, SystemSnapshot::from_path(after)?)));
+    match snapshots {
+        Ok((before, after)) => {
+            let diff = SnapshotDiff::between(&before, &after);
+            match options.format {
+                Format::Table => print!("{}", diff),
+                format => {
+                    let value = serde_json::to_value(&diff).unwrap_or(Value::Null);
+                    print!("{}", output::render(format, "diff", &value));
+                }
+            }
+            if diff.is_empty() { ExitCode::SUCCESS } else { ExitCode::from(1) }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(2)
+        }
+    }
 }
@@ -0,0 +1,252 @@
+// Maps processor identification to the name of its core design, so machines can be grouped by
+// microarchitecture rather than by marketing name. Rows are checked in order, so the ones that
+// narrow a model down by stepping come before the catch-all row for that model.
+
+// (vendor, family, first model, last model, first stepping, last stepping, name)
+type X86Row = (&'static str, u32, u32, u32, u32, u32, &'static str);
+
+const X86: &[X86Row] = &[
+    // Intel Core and Xeon, family 6
+    ("GenuineIntel", 6, 0x0F, 0x0F, 0, 15, "Merom"),
+    ("GenuineIntel", 6, 0x16, 0x16, 0, 15, "Merom"),
+    ("GenuineIntel", 6, 0x17, 0x17, 0, 15, "Penryn"),
+    ("GenuineIntel", 6, 0x1D, 0x1D, 0, 15, "Penryn"),
+    ("GenuineIntel", 6, 0x1A, 0x1A, 0, 15, "Nehalem"),
+    ("GenuineIntel", 6, 0x1E, 0x1F, 0, 15, "Nehalem"),
+    ("GenuineIntel", 6, 0x2E, 0x2E, 0, 15, "Nehalem"),
+    ("GenuineIntel", 6, 0x25, 0x25, 0, 15, "Westmere"),
+    ("GenuineIntel", 6, 0x2C, 0x2C, 0, 15, "Westmere"),
+    ("GenuineIntel", 6, 0x2F, 0x2F, 0, 15, "Westmere"),
+    ("GenuineIntel", 6, 0x2A, 0x2A, 0, 15, "Sandy Bridge"),
+    ("GenuineIntel", 6, 0x2D, 0x2D, 0, 15, "Sandy Bridge"),
+    ("GenuineIntel", 6, 0x3A, 0x3A, 0, 15, "Ivy Bridge"),
+    ("GenuineIntel", 6, 0x3E, 0x3E, 0, 15, "Ivy Bridge"),
+    ("GenuineIntel", 6, 0x3C, 0x3C, 0, 15, "Haswell"),
+    ("GenuineIntel", 6, 0x3F, 0x3F, 0, 15, "Haswell"),
+    ("GenuineIntel", 6, 0x45, 0x46, 0, 15, "Haswell"),
+    ("GenuineIntel", 6, 0x3D, 0x3D, 0, 15, "Broadwell"),
+    ("GenuineIntel", 6, 0x47, 0x47, 0, 15, "Broadwell"),
+    ("GenuineIntel", 6, 0x4F, 0x4F, 0, 15, "Broadwell"),
+    ("GenuineIntel", 6, 0x56, 0x56, 0, 15, "Broadwell"),
+    ("GenuineIntel", 6, 0x4E, 0x4E, 0, 15, "Skylake"),
+    ("GenuineIntel", 6, 0x5E, 0x5E, 0, 15, "Skylake"),
+    ("GenuineIntel", 6, 0x55, 0x55, 5, 7, "Cascade Lake"),
+    ("GenuineIntel", 6, 0x55, 0x55, 10, 11, "Cooper Lake"),
+    ("GenuineIntel", 6, 0x55, 0x55, 0, 15, "Skylake"),
+    ("GenuineIntel", 6, 0x8E, 0x8E, 11, 12, "Whiskey Lake"),
+    ("GenuineIntel", 6, 0x8E, 0x8E, 0, 15, "Kaby Lake"),
+    ("GenuineIntel", 6, 0x9E, 0x9E, 10, 13, "Coffee Lake"),
+    ("GenuineIntel", 6, 0x9E, 0x9E, 0, 15, "Kaby Lake"),
+    ("GenuineIntel", 6, 0xA5, 0xA6, 0, 15, "Comet Lake"),
+    ("GenuineIntel", 6, 0x66, 0x66, 0, 15, "Cannon Lake"),
+    ("GenuineIntel", 6, 0x7D, 0x7E, 0, 15, "Ice Lake"),
+    ("GenuineIntel", 6, 0x6A, 0x6A, 0, 15, "Ice Lake"),
+    ("GenuineIntel", 6, 0x6C, 0x6C, 0, 15, "Ice Lake"),
+    ("GenuineIntel", 6, 0x8C, 0x8D, 0, 15, "Tiger Lake"),
+    ("GenuineIntel", 6, 0xA7, 0xA7, 0, 15, "Rocket Lake"),
+    ("GenuineIntel", 6, 0x97, 0x97, 0, 15, "Alder Lake"),
+    ("GenuineIntel", 6, 0x9A, 0x9A, 0, 15, "Alder Lake"),
+    ("GenuineIntel", 6, 0xBE, 0xBE, 0, 15, "Gracemont"),
+    ("GenuineIntel", 6, 0xB7, 0xB7, 0, 15, "Raptor Lake"),
+    ("GenuineIntel", 6, 0xBA, 0xBA, 0, 15, "Raptor Lake"),
+    ("GenuineIntel", 6, 0xBF, 0xBF, 0, 15, "Raptor Lake"),
+    ("GenuineIntel", 6, 0x8F, 0x8F, 0, 15, "Sapphire Rapids"),
+    ("GenuineIntel", 6, 0xCF, 0xCF, 0, 15, "Emerald Rapids"),
+    ("GenuineIntel", 6, 0xAA, 0xAC, 0, 15, "Meteor Lake"),
+    ("GenuineIntel", 6, 0xBD, 0xBD, 0, 15, "Lunar Lake"),
+    ("GenuineIntel", 6, 0xC5, 0xC6, 0, 15, "Arrow Lake"),
+    ("GenuineIntel", 6, 0xAD, 0xAE, 0, 15, "Granite Rapids"),
+    ("GenuineIntel", 6, 0xAF, 0xAF, 0, 15, "Sierra Forest"),
+    // Intel Atom and Xeon Phi, family 6
+    ("GenuineIntel", 6, 0x1C, 0x1C, 0, 15, "Bonnell"),
+    ("GenuineIntel", 6, 0x26, 0x26, 0, 15, "Bonnell"),
+    ("GenuineIntel", 6, 0x37, 0x37, 0, 15, "Silvermont"),
+    ("GenuineIntel", 6, 0x4D, 0x4D, 0, 15, "Silvermont"),
+    ("GenuineIntel", 6, 0x4C, 0x4C, 0, 15, "Airmont"),
+    ("GenuineIntel", 6, 0x5C, 0x5C, 0, 15, "Goldmont"),
+    ("GenuineIntel", 6, 0x5F, 0x5F, 0, 15, "Goldmont"),
+    ("GenuineIntel", 6, 0x7A, 0x7A, 0, 15, "Goldmont Plus"),
+    ("GenuineIntel", 6, 0x86, 0x86, 0, 15, "Tremont"),
+    ("GenuineIntel", 6, 0x96, 0x96, 0, 15, "Tremont"),
+    ("GenuineIntel", 6, 0x9C, 0x9C, 0, 15, "Tremont"),
+    ("GenuineIntel", 6, 0x57, 0x57, 0, 15, "Knights Landing"),
+    ("GenuineIntel", 6, 0x85, 0x85, 0, 15, "Knights Mill"),
+    ("GenuineIntel", 0xF, 0x00, 0xFF, 0, 15, "NetBurst"),
+    // AMD
+    ("AuthenticAMD", 0x0F, 0x00, 0xFF, 0, 15, "K8"),
+    ("AuthenticAMD", 0x10, 0x00, 0xFF, 0, 15, "K10"),
+    ("AuthenticAMD", 0x14, 0x00, 0xFF, 0, 15, "Bobcat"),
+    ("AuthenticAMD", 0x15, 0x00, 0x01, 0, 15, "Bulldozer"),
+    ("AuthenticAMD", 0x15, 0x02, 0x1F, 0, 15, "Piledriver"),
+    ("AuthenticAMD", 0x15, 0x30, 0x3F, 0, 15, "Steamroller"),
+    ("AuthenticAMD", 0x15, 0x60, 0x7F, 0, 15, "Excavator"),
+    ("AuthenticAMD", 0x16, 0x00, 0x0F, 0, 15, "Jaguar"),
+    ("AuthenticAMD", 0x16, 0x30, 0x3F, 0, 15, "Puma"),
+    ("AuthenticAMD", 0x17, 0x08, 0x08, 0, 15, "Zen+"),
+    ("AuthenticAMD", 0x17, 0x18, 0x18, 0, 15, "Zen+"),
+    ("AuthenticAMD", 0x17, 0x00, 0x2F, 0, 15, "Zen"),
+    ("AuthenticAMD", 0x17, 0x30, 0xAF, 0, 15, "Zen 2"),
+    ("AuthenticAMD", 0x19, 0x10, 0x1F, 0, 15, "Zen 4"),
+    ("AuthenticAMD", 0x19, 0x40, 0x4F, 0, 15, "Zen 3+"),
+    ("AuthenticAMD", 0x19, 0x60, 0x7F, 0, 15, "Zen 4"),
+    ("AuthenticAMD", 0x19, 0xA0, 0xAF, 0, 15, "Zen 4c"),
+    ("AuthenticAMD", 0x19, 0x00, 0x5F, 0, 15, "Zen 3"),
+    ("AuthenticAMD", 0x1A, 0x00, 0xFF, 0, 15, "Zen 5"),
+    ("HygonGenuine", 0x18, 0x00, 0xFF, 0, 15, "Dhyana"),
+];
+
+// (MIDR implementer, part number, name)
+const ARM: &[(u8, u16, &str)] = &[
+    (0x41, 0xc05, "Cortex-A5"),
+    (0x41, 0xc07, "Cortex-A7"),
+    (0x41, 0xc08, "Cortex-A8"),
+    (0x41, 0xc09, "Cortex-A9"),
+    (0x41, 0xc0d, "Cortex-A12"),
+    (0x41, 0xc0e, "Cortex-A17"),
+    (0x41, 0xc0f, "Cortex-A15"),
+    (0x41, 0xd01, "Cortex-A32"),
+    (0x41, 0xd02, "Cortex-A34"),
+    (0x41, 0xd03, "Cortex-A53"),
+    (0x41, 0xd04, "Cortex-A35"),
+    (0x41, 0xd05, "Cortex-A55"),
+    (0x41, 0xd06, "Cortex-A65"),
+    (0x41, 0xd07, "Cortex-A57"),
+    (0x41, 0xd08, "Cortex-A72"),
+    (0x41, 0xd09, "Cortex-A73"),
+    (0x41, 0xd0a, "Cortex-A75"),
+    (0x41, 0xd0b, "Cortex-A76"),
+    (0x41, 0xd0c, "Neoverse N1"),
+    (0x41, 0xd0d, "Cortex-A77"),
+    (0x41, 0xd0e, "Cortex-A76AE"),
+    (0x41, 0xd40, "Neoverse V1"),
+    (0x41, 0xd41, "Cortex-A78"),
+    (0x41, 0xd42, "Cortex-A78AE"),
+    (0x41, 0xd44, "Cortex-X1"),
+    (0x41, 0xd46, "Cortex-A510"),
+    (0x41, 0xd47, "Cortex-A710"),
+    (0x41, 0xd48, "Cortex-X2"),
+    (0x41, 0xd49, "Neoverse N2"),
+    (0x41, 0xd4a, "Neoverse E1"),
+    (0x41, 0xd4b, "Cortex-A78C"),
+    (0x41, 0xd4d, "Cortex-A715"),
+    (0x41, 0xd4e, "Cortex-X3"),
+    (0x41, 0xd4f, "Neoverse V2"),
+    (0x41, 0xd80, "Cortex-A520"),
+    (0x41, 0xd81, "Cortex-A720"),
+    (0x41, 0xd82, "Cortex-X4"),
+    (0x41, 0xd84, "Neoverse V3"),
+    (0x41, 0xd8e, "Neoverse N3"),
+    (0x42, 0x516, "Vulcan"),
+    (0x43, 0x0a1, "ThunderX"),
+    (0x43, 0x0af, "ThunderX2"),
+    (0x43, 0x0b8, "ThunderX3"),
+    (0x46, 0x001, "A64FX"),
+    (0x48, 0xd01, "TaiShan v110"),
+    (0x4e, 0x003, "Denver 2"),
+    (0x4e, 0x004, "Carmel"),
+    (0x50, 0x000, "X-Gene"),
+    (0x51, 0x800, "Kryo 2xx Gold"),
+    (0x51, 0x801, "Kryo 2xx Silver"),
+    (0x51, 0x802, "Kryo 3xx Gold"),
+    (0x51, 0x803, "Kryo 3xx Silver"),
+    (0x51, 0x804, "Kryo 4xx Gold"),
+    (0x51, 0x805, "Kryo 4xx Silver"),
+    (0x51, 0xc00, "Falkor"),
+    (0x51, 0x001, "Oryon"),
+    (0x53, 0x001, "Exynos M1"),
+    (0x61, 0x022, "Icestorm"),
+    (0x61, 0x023, "Firestorm"),
+    (0x61, 0x032, "Blizzard"),
+    (0x61, 0x033, "Avalanche"),
+    (0xc0, 0xac3, "Ampere-1"),
+    (0xc0, 0xac4, "Ampere-1a"),
+];
+
+// The implementer field of MIDR_EL1, as assigned by Arm
+const ARM_IMPLEMENTERS: &[(u8, &str)] = &[
+    (0x41, "ARM"),
+    (0x42, "Broadcom"),
+    (0x43, "Cavium"),
+    (0x44, "DEC"),
+    (0x46, "Fujitsu"),
+    (0x48, "HiSilicon"),
+    (0x49, "Infineon"),
+    (0x4d, "Motorola/Freescale"),
+    (0x4e, "NVIDIA"),
+    (0x50, "APM"),
+    (0x51, "Qualcomm"),
+    (0x53, "Samsung"),
+    (0x56, "Marvell"),
+    (0x61, "Apple"),
+    (0x66, "Faraday"),
+    (0x69, "Intel"),
+    (0x6d, "Microsoft"),
+    (0x70, "Phytium"),
+    (0xc0, "Ampere"),
+];
+
+// RISC-V mvendorid values, which are JEDEC manufacturer IDs
+const RISCV_VENDORS: &[(u64, &str)] = &[
+    (0x489, "SiFive"),
+    (0x5b7, "T-Head"),
+    (0x31e, "Andes"),
+];
+
+// The small, in-order designs that make up the LITTLE cluster of big.LITTLE and DynamIQ systems
+const ARM_EFFICIENCY: &[(u8, u16)] = &[
+    (0x41, 0xc05), // Cortex-A5
+    (0x41, 0xc07), // Cortex-A7
+    (0x41, 0xd01), // Cortex-A32
+    (0x41, 0xd02), // Cortex-A34
+    (0x41, 0xd03), // Cortex-A53
+    (0x41, 0xd04), // Cortex-A35
+    (0x41, 0xd05), // Cortex-A55
+    (0x41, 0xd46), // Cortex-A510
+    (0x41, 0xd80), // Cortex-A520
+    (0x51, 0x801), // Kryo 2xx Silver
+    (0x51, 0x803), // Kryo 3xx Silver
+    (0x51, 0x805), // Kryo 4xx Silver
+    (0x61, 0x022), // Icestorm
+    (0x61, 0x032), // Blizzard
+];
+
+// From the CPUID vendor string and display family, model and stepping
+pub fn x86(vendor: &str, family: u32, model: u32, stepping: u32) -> Option<&'static str> {
+    X86.iter()
+        .find(|&&(row_vendor, row_family, first, last, min_stepping, max_stepping, _)| {
+            row_vendor == vendor
+                && row_family == family
+                && (first..=last).contains(&model)
+                && (min_stepping..=max_stepping).contains(&stepping)
+        })
+        .map(|row| row.6)
+}
+
+// From the implementer and part number fields of the MIDR_EL1 register
+pub fn arm(implementer: u8, part: u16) -> Option<&'static str> {
+    ARM.iter()
+        .find(|&&(row_implementer, row_part, _)| row_implementer == implementer && row_part == part)
+        .map(|row| row.2)
+}
+
+pub fn arm_implementer(implementer: u8) -> Option<&'static str> {
+    ARM_IMPLEMENTERS.iter().find(|&&(id, _)| id == implementer).map(|row| row.1)
+}
+
+pub fn riscv_vendor(mvendorid: u64) -> Option<&'static str> {
+    RISCV_VENDORS.iter().find(|&&(id, _)| id == mvendorid).map(|row| row.1)
+}
+
+pub fn arm_is_efficiency_core(implementer: u8, part: u16) -> bool {
+    ARM_EFFICIENCY.contains(&(implementer, part))
+}
+
+// Reads family, model and stepping out of a Win32_Processor description such as
+// "Intel64 Family 6 Model 158 Stepping 10"
+pub(crate) fn parse_x86_description(description: &str) -> Option<(u32, u32, u32)> {
+    let mut words = description.split_whitespace();
+    let mut field = |name: &str| {
+        words.by_ref().skip_while(|word| *word != name).nth(1).and_then(|value| value.parse::<u32>().ok())
+    };
+    Some((field("Family")?, field("Model")?, field("Stepping")?))
+}
@@ -0,0 +1,350 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use hysterical::{ByteSize, Frequency};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            _ => Err(format!("unknown format '{}', expected table, json, yaml or toml", s)),
+        }
+    }
+}
+
+// Keeps only the given dotted field paths of every record. A list is treated as a list of
+// records, anything else as a single record.
+pub fn select(value: Value, fields: &[String]) -> Value {
+    if fields.is_empty() {
+        return value;
+    }
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(|item| select_record(&item, fields)).collect()),
+        record => select_record(&record, fields),
+    }
+}
+
+fn select_record(record: &Value, fields: &[String]) -> Value {
+    let mut selected = Map::new();
+    for field in fields {
+        let path: Vec<&str> = field.split('.').collect();
+        if let Some(value) = path.iter().try_fold(record, |value, key| value.get(key)) {
+            insert_path(&mut selected, &path, value.clone());
+        }
+    }
+    Value::Object(selected)
+}
+
+fn insert_path(map: &mut Map<String, Value>, path: &[&str], value: Value) {
+    match path {
+        [] => {}
+        [key] => {
+            map.insert(key.to_string(), value);
+        }
+        [key, rest @ ..] => {
+            let child = map.entry(key.to_string()).or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(child) = child {
+                insert_path(child, rest, value);
+            }
+        }
+    }
+}
+
+// `name` labels the data where the format needs a root key (TOML) or a heading (table)
+pub fn render(format: Format, name: &str, value: &Value) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Json => {
+            out = serde_json::to_string_pretty(value).unwrap_or_default();
+            out.push('\n');
+        }
+        Format::Yaml => {
+            out.push_str("---\n");
+            yaml(value, 0, &mut out);
+        }
+        Format::Toml => {
+            let root = match value {
+                Value::Object(map) => map.clone(),
+                other => Map::from_iter([(name.to_string(), other.clone())]),
+            };
+            toml_table(&[], &root, &mut out);
+        }
+        Format::Table => table(name, value, &mut out),
+    }
+    out
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Array(items) => items.iter().map(scalar).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+// Fields that serialise a Frequency or ByteSize as a plain number. The table shows them with
+// their units, the data formats keep the raw numbers.
+const FREQUENCY_FIELDS: &[&str] = &["frequency"];
+const BYTE_FIELDS: &[&str] = &[
+    "l1", "l2", "l3", "memory", "total_memory", "free_memory", "total", "available", "free",
+    "cached", "buffers", "swap_total", "swap_used", "committed", "commit_limit",
+];
+
+// A table cell for the value at a dotted field path
+fn cell(path: &str, value: &Value) -> String {
+    let field = path.rsplit('.').next().unwrap_or(path);
+    match value.as_u64() {
+        Some(hz) if FREQUENCY_FIELDS.contains(&field) => Frequency::from_hz(hz).to_string(),
+        Some(bytes) if BYTE_FIELDS.contains(&field) => ByteSize::from_bytes(bytes).to_string(),
+        _ => scalar(value),
+    }
+}
+
+fn is_records(value: &Value) -> bool {
+    matches!(value, Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object))
+}
+
+// Flattens nested objects into dotted columns, e.g. cache_size.l2
+fn columns(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                columns(&path, value, out);
+            }
+        }
+        other => out.push((prefix.to_string(), cell(prefix, other))),
+    }
+}
+
+// Lists of records become one row per record, objects a two column key/value table, and
+// lists of records nested in an object (e.g. a snapshot's cpus) get a section of their own.
+fn table(name: &str, value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) if items.is_empty() => {
+            let _ = writeln!(out, "{}: none", name);
+        }
+        Value::Array(items) => {
+            let rows: Vec<Vec<(String, String)>> = items
+                .iter()
+                .map(|item| {
+                    let mut row = Vec::new();
+                    columns("", item, &mut row);
+                    row
+                })
+                .collect();
+            let mut headers: Vec<String> = Vec::new();
+            for (header, _) in rows.iter().flatten() {
+                if !headers.contains(header) {
+                    headers.push(header.clone());
+                }
+            }
+            let cells: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| {
+                    headers
+                        .iter()
+                        .map(|header| row.iter().find(|(key, _)| key == header).map(|(_, value)| value.clone()).unwrap_or_default())
+                        .collect()
+                })
+                .collect();
+            grid(&headers, &cells, out);
+        }
+        Value::Object(map) => {
+            let mut rows = Vec::new();
+            let mut sections = Vec::new();
+            for (key, value) in map {
+                if is_records(value) || matches!(value, Value::Array(items) if items.is_empty()) {
+                    sections.push((key, value));
+                } else {
+                    columns(key, value, &mut rows);
+                }
+            }
+            if !rows.is_empty() {
+                let headers = [String::from("field"), String::from("value")];
+                let cells: Vec<Vec<String>> = rows.into_iter().map(|(key, value)| vec![key, value]).collect();
+                grid(&headers, &cells, out);
+            }
+            for (key, value) in sections {
+                out.push('\n');
+                table(key, value, out);
+            }
+        }
+        other => {
+            let _ = writeln!(out, "{}", scalar(other));
+        }
+    }
+}
+
+fn grid(headers: &[String], rows: &[Vec<String>], out: &mut String) {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| rows.iter().map(|row| row[i].chars().count()).chain([header.chars().count()]).max().unwrap_or(0))
+        .collect();
+    let line = |cells: Vec<&str>, out: &mut String| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        let _ = writeln!(out, "{}", padded.join("  ").trim_end());
+    };
+    line(headers.iter().map(String::as_str).collect(), out);
+    line(widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().iter().map(String::as_str).collect(), out);
+    for row in rows {
+        line(row.iter().map(String::as_str).collect(), out);
+    }
+}
+
+// Strings are always double quoted, which YAML accepts with JSON escaping
+fn yaml(value: &Value, indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let _ = write!(out, "{}{}:", pad, yaml_key(key));
+                yaml_child(value, indent, out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                let _ = write!(out, "{}-", pad);
+                match item {
+                    Value::Object(map) if !map.is_empty() => {
+                        // The first key shares the dash's line, the rest line up below it
+                        let mut nested = String::new();
+                        yaml(item, indent + 2, &mut nested);
+                        let _ = write!(out, " {}", &nested[indent + 2..]);
+                    }
+                    Value::Array(inner) if !inner.is_empty() => {
+                        out.push('\n');
+                        yaml(item, indent + 2, out);
+                    }
+                    other => {
+                        let _ = writeln!(out, " {}", yaml_scalar(other));
+                    }
+                }
+            }
+        }
+        other => {
+            let _ = writeln!(out, "{}{}", pad, yaml_scalar(other));
+        }
+    }
+}
+
+fn yaml_child(value: &Value, indent: usize, out: &mut String) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            yaml(value, indent + 2, out);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            yaml(value, indent + 2, out);
+        }
+        other => {
+            let _ = writeln!(out, " {}", yaml_scalar(other));
+        }
+    }
+}
+
+fn yaml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        Value::from(key).to_string()
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Object(_) => String::from("{}"),
+        Value::Array(_) => String::from("[]"),
+        other => other.to_string(),
+    }
+}
+
+// Writes the plain values of a table first, then its sub-tables and arrays of tables.
+// TOML has no null, so null values are left out.
+fn toml_table(path: &[String], map: &Map<String, Value>, out: &mut String) {
+    for (key, value) in map {
+        if !value.is_null() && !value.is_object() && !is_records(value) {
+            let _ = writeln!(out, "{} = {}", toml_key(key), toml_value(value));
+        }
+    }
+    for (key, value) in map {
+        let mut child = path.to_vec();
+        child.push(toml_key(key));
+        match value {
+            Value::Object(inner) => {
+                let _ = writeln!(out, "\n[{}]", child.join("."));
+                toml_table(&child, inner, out);
+            }
+            Value::Array(items) if is_records(value) => {
+                for item in items {
+                    let _ = writeln!(out, "\n[[{}]]", child.join("."));
+                    if let Value::Object(inner) = item {
+                        toml_table(&child, inner, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn toml_key(key: &str) -> String {
+    yaml_key(key)
+}
+
+fn toml_value(value: &Value) -> String {
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter(|item| !item.is_null()).map(toml_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let items: Vec<String> = map
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| format!("{} = {}", toml_key(key), toml_value(value)))
+                .collect();
+            format!("{{ {} }}", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn table_shows_units_and_data_formats_keep_numbers() {
+        let value = json!([{ "frequency": 3_600_000_000u64, "cache_size": { "l1": 81_920, "l3": null }, "cores": 4 }]);
+        let table = render(Format::Table, "cpu", &value);
+        assert!(table.contains("3.60 GHz"), "{}", table);
+        assert!(table.contains("80 KiB"), "{}", table);
+        assert!(table.lines().nth(2).unwrap().ends_with('4'), "{}", table);
+        let json = render(Format::Json, "cpu", &value);
+        assert!(json.contains("3600000000") && json.contains("81920"));
+        assert!(render(Format::Toml, "cpu", &value).contains("frequency = 3600000000"));
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use crate::Error;
+
+// A parsed copy of the pci.ids database (https://pci-ids.ucw.cz/), used to turn the numeric
+// vendor and device IDs exposed by the kernel into names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PciIds {
+    pub vendors: BTreeMap<u16, Vendor>,
+    pub classes: BTreeMap<u8, Class>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vendor {
+    pub name: String,
+    pub devices: BTreeMap<u16, Device>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    pub name: String,
+    pub subsystems: BTreeMap<(u16, u16), String>, // Keyed by (subvendor, subdevice)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class {
+    pub name: String,
+    pub subclasses: BTreeMap<u8, Subclass>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subclass {
+    pub name: String,
+    pub prog_ifs: BTreeMap<u8, String>,
+}
+
+impl PciIds {
+    pub fn parse(content: &str) -> Result<PciIds, Error> {
+        let mut ids = PciIds::default();
+        // The vendor/device or class/subclass the next indented line belongs to
+        let mut vendor: Option<(u16, Option<u16>)> = None;
+        let mut class: Option<(u8, Option<u8>)> = None;
+
+        for (number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let error = || Error::parse(format!("pci.ids line {}", number + 1), line.trim());
+            let depth = line.chars().take_while(|&c| c == '\t').count();
+            let line = &line[depth..];
+
+            match depth {
+                0 if line.starts_with("C ") => {
+                    let (id, name) = split_entry(&line[2..]).ok_or_else(error)?;
+                    let id = u8::from_str_radix(id, 16).map_err(|_| error())?;
+                    ids.classes.insert(id, Class { name, subclasses: BTreeMap::new() });
+                    class = Some((id, None));
+                    vendor = None;
+                }
+                0 => {
+                    let (id, name) = split_entry(line).ok_or_else(error)?;
+                    let id = u16::from_str_radix(id, 16).map_err(|_| error())?;
+                    ids.vendors.insert(id, Vendor { name, devices: BTreeMap::new() });
+                    vendor = Some((id, None));
+                    class = None;
+                }
+                1 => {
+                    let (id, name) = split_entry(line).ok_or_else(error)?;
+                    if let Some((vendor_id, device)) = vendor.as_mut() {
+                        let id = u16::from_str_radix(id, 16).map_err(|_| error())?;
+                        let devices = &mut ids.vendors.get_mut(vendor_id).ok_or_else(error)?.devices;
+                        devices.insert(id, Device { name, subsystems: BTreeMap::new() });
+                        *device = Some(id);
+                    } else if let Some((class_id, subclass)) = class.as_mut() {
+                        let id = u8::from_str_radix(id, 16).map_err(|_| error())?;
+                        let subclasses = &mut ids.classes.get_mut(class_id).ok_or_else(error)?.subclasses;
+                        subclasses.insert(id, Subclass { name, prog_ifs: BTreeMap::new() });
+                        *subclass = Some(id);
+                    } else {
+                        return Err(error());
+                    }
+                }
+                2 => {
+                    if let Some((vendor_id, Some(device_id))) = vendor {
+                        let (ids_part, name) = split_entry(line).ok_or_else(error)?;
+                        let (subvendor, subdevice) = ids_part.split_once(' ').ok_or_else(error)?;
+                        let subvendor = u16::from_str_radix(subvendor, 16).map_err(|_| error())?;
+                        let subdevice = u16::from_str_radix(subdevice.trim(), 16).map_err(|_| error())?;
+                        ids.vendors
+                            .get_mut(&vendor_id)
+                            .and_then(|vendor| vendor.devices.get_mut(&device_id))
+                            .ok_or_else(error)?
+                            .subsystems
+                            .insert((subvendor, subdevice), name);
+                    } else if let Some((class_id, Some(subclass_id))) = class {
+                        let (id, name) = split_entry(line).ok_or_else(error)?;
+                        let id = u8::from_str_radix(id, 16).map_err(|_| error())?;
+                        ids.classes
+                            .get_mut(&class_id)
+                            .and_then(|class| class.subclasses.get_mut(&subclass_id))
+                            .ok_or_else(error)?
+                            .prog_ifs
+                            .insert(id, name);
+                    } else {
+                        return Err(error());
+                    }
+                }
+                _ => return Err(error()),
+            }
+        }
+
+        Ok(ids)
+    }
+
+    // The trimmed snapshot compiled into the crate. Covers the common display adapter vendors only.
+    pub fn embedded() -> &'static PciIds {
+        static EMBEDDED: OnceLock<PciIds> = OnceLock::new();
+        EMBEDDED.get_or_init(|| PciIds::parse(include_str!("../data/pci.ids")).unwrap_or_default())
+    }
+
+    // A full copy of the database, e.g. the /usr/share/hwdata/pci.ids most distributions install
+    pub fn load(path: &Path) -> Result<PciIds, Error> {
+        let content = fs::read_to_string(path).map_err(|err| Error::io(path.display().to_string(), err))?;
+        PciIds::parse(&content)
+    }
+
+    pub fn vendor_name(&self, vendor: u16) -> Option<&str> {
+        self.vendors.get(&vendor).map(|vendor| vendor.name.as_str())
+    }
+
+    pub fn device_name(&self, vendor: u16, device: u16) -> Option<&str> {
+        self.vendors.get(&vendor)?.devices.get(&device).map(|device| device.name.as_str())
+    }
+
+    pub fn subsystem_name(&self, vendor: u16, device: u16, subvendor: u16, subdevice: u16) -> Option<&str> {
+        self.vendors
+            .get(&vendor)?
+            .devices
+            .get(&device)?
+            .subsystems
+            .get(&(subvendor, subdevice))
+            .map(String::as_str)
+    }
+
+    // The most specific name known for a class code, e.g. (0x03, 0x00, 0x00) -> "VGA controller"
+    pub fn class_name(&self, class: u8, subclass: u8, prog_if: u8) -> Option<&str> {
+        let class = self.classes.get(&class)?;
+        let Some(sub) = class.subclasses.get(&subclass) else {
+            return Some(&class.name);
+        };
+        Some(sub.prog_ifs.get(&prog_if).unwrap_or(&sub.name))
+    }
+}
+
+// Splits "10de  NVIDIA Corporation" into its ID and name
+fn split_entry(line: &str) -> Option<(&str, String)> {
+    let (id, name) = line.split_once("  ")?;
+    Some((id.trim(), name.trim().to_string()))
+}
+
+// Parses the "0x10de" style IDs found in sysfs
+pub(crate) fn parse_hex_id(value: &str) -> Option<u16> {
+    u16::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_names_common_display_controllers() {
+        let ids = PciIds::embedded();
+        assert_eq!(ids.device_name(0x10de, 0x2684), Some("AD102 [GeForce RTX 4090]"));
+        assert_eq!(ids.device_name(0x1002, 0x744c), Some("Navi 31 [Radeon RX 7900 XT/7900 XTX/7900 GRE/7900M]"));
+        assert_eq!(ids.device_name(0x8086, 0x3e92), Some("CoffeeLake-S GT2 [UHD Graphics 630]"));
+        assert_eq!(ids.vendor_name(0x1234), Some("Technical Corp."));
+        assert_eq!(ids.class_name(0x03, 0x00, 0x00), Some("VGA controller"));
+    }
+
+    #[test]
+    fn load_reads_a_database_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/pci.ids");
+        assert_eq!(&PciIds::load(&path).unwrap(), PciIds::embedded());
+        assert!(matches!(PciIds::load(&path.with_extension("missing")), Err(Error::SourceUnavailable { .. })));
+    }
+}
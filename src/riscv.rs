@@ -0,0 +1,200 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::Error;
+
+// A RISC-V ISA string such as "rv64imafdch_zicsr_zifencei_zba_zbb", as Linux prints it in the
+// "isa" line of /proc/cpuinfo. Single-letter extensions come first in canonical order, then
+// the multi-letter ones (Z*, S*, X*) separated by underscores. Version numbers ("2p1") are
+// dropped and G is expanded to IMAFD plus Zicsr and Zifencei.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RiscvIsa {
+    pub xlen: u32,               // Register width: 32, 64 or 128
+    pub base: char,              // 'i', or 'e' for the reduced embedded base
+    pub extensions: Vec<String>, // Lowercase, e.g. "m", "v", "zba"
+}
+
+// What the G shorthand stands for on top of the I base
+const GENERAL: &[&str] = &["m", "a", "f", "d", "zicsr", "zifencei"];
+
+impl RiscvIsa {
+    pub fn parse(isa: &str) -> Result<RiscvIsa, Error> {
+        let error = |reason: &str| Error::parse("isa", format!("'{}': {}", isa, reason));
+        let lower = isa.trim().to_ascii_lowercase();
+        let rest = lower.strip_prefix("rv").ok_or_else(|| error("expected an 'rv' prefix"))?;
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let xlen = match &rest[..digits] {
+            "32" => 32,
+            "64" => 64,
+            "128" => 128,
+            _ => return Err(error("expected a register width of 32, 64 or 128")),
+        };
+
+        let mut segments = rest[digits..].split('_').filter(|segment| !segment.is_empty());
+        let first = segments.next().ok_or_else(|| error("missing the base integer ISA"))?;
+        let mut extensions: Vec<String> = Vec::new();
+        let mut add = |extension: &str| {
+            if !extensions.iter().any(|known| known == extension) {
+                extensions.push(extension.to_string());
+            }
+        };
+
+        let base = match first.chars().next() {
+            Some('i') => 'i',
+            Some('e') => 'e',
+            Some('g') => {
+                GENERAL.iter().for_each(|extension| add(extension));
+                'i'
+            }
+            _ => return Err(error("the base integer ISA must be I, E or G")),
+        };
+
+        // Single letters run until the first multi-letter extension, which may follow them
+        // without an underscore
+        let mut multi_letter: Vec<&str> = Vec::new();
+        let singles = &first[1..];
+        let split = singles.find(['z', 's', 'x']).unwrap_or(singles.len());
+        let mut single_letters = |letters: &str| -> Result<(), Error> {
+            for letter in strip_versions(letters).chars() {
+                if !letter.is_ascii_alphabetic() {
+                    return Err(error(&format!("malformed extension '{}'", letter)));
+                }
+                if letter == 'g' {
+                    GENERAL.iter().for_each(|extension| add(extension));
+                } else {
+                    add(letter.encode_utf8(&mut [0; 4]));
+                }
+            }
+            Ok(())
+        };
+        single_letters(&singles[..split])?;
+        if split < singles.len() {
+            multi_letter.push(&singles[split..]);
+        }
+        for segment in segments {
+            if segment.starts_with(['z', 's', 'x']) {
+                multi_letter.push(segment);
+            } else {
+                single_letters(segment)?;
+            }
+        }
+        for extension in multi_letter {
+            let name = strip_version(extension);
+            if name.len() < 2 || !name.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                return Err(error(&format!("malformed extension '{}'", extension)));
+            }
+            add(name);
+        }
+
+        Ok(RiscvIsa { xlen, base, extensions })
+    }
+
+    // Case-insensitive, e.g. has_extension("V") or has_extension("Zbb")
+    pub fn has_extension(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.extensions.contains(&name)
+    }
+
+    // RV64GC and friends: whether the IMAFD, Zicsr and Zifencei extensions are all present
+    pub fn is_general_purpose(&self) -> bool {
+        self.base == 'i' && GENERAL.iter().all(|extension| self.has_extension(extension))
+    }
+}
+
+// Drops version numbers such as the "2p1" in "i2p1m2p0"
+fn strip_versions(letters: &str) -> String {
+    let bytes = letters.as_bytes();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_digit() {
+            i = skip_version(bytes, i);
+        } else {
+            out.push(bytes[i] as char);
+            i += 1;
+        }
+    }
+    out
+}
+
+// The multi-letter name without its version, e.g. "zicsr" for "zicsr2p0"
+fn strip_version(extension: &str) -> &str {
+    let bytes = extension.as_bytes();
+    let start = (1..bytes.len()).find(|&i| bytes[i].is_ascii_digit() && skip_version(bytes, i) == bytes.len());
+    &extension[..start.unwrap_or(bytes.len())]
+}
+
+// Where a version ("2", or "2p1") starting at `i` ends
+fn skip_version(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    if i + 1 < bytes.len() && bytes[i] == b'p' && bytes[i + 1].is_ascii_digit() {
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+    i
+}
+
+impl fmt::Display for RiscvIsa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rv{}{}", self.xlen, self.base)?;
+        let (single, multi): (Vec<&String>, Vec<&String>) = self.extensions.iter().partition(|extension| extension.len() == 1);
+        for extension in single {
+            f.write_str(extension)?;
+        }
+        for extension in multi {
+            write!(f, "_{}", extension)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RiscvIsa {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RiscvIsa::parse(s)
+    }
+}
+
+impl Serialize for RiscvIsa {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RiscvIsa {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let isa = RiscvIsa::parse("rv64imafdch_zicsr_zifencei_zba_zbb_sstc").unwrap();
+        assert_eq!((isa.xlen, isa.base), (64, 'i'));
+        assert!(isa.is_general_purpose());
+        assert!(!isa.has_extension("V") && isa.has_extension("Zbb") && isa.has_extension("h"));
+        assert_eq!(isa.to_string(), "rv64imafdch_zicsr_zifencei_zba_zbb_sstc");
+
+        let versioned = RiscvIsa::parse("rv64i2p1m2p0a2p1f2p2d2p2c2p0zicsr2p0").unwrap();
+        assert_eq!(versioned.extensions, ["m", "a", "f", "d", "c", "zicsr"]);
+        assert_eq!(RiscvIsa::parse("RV32GC").unwrap().to_string(), "rv32imafdc_zicsr_zifencei");
+        assert_eq!(RiscvIsa::parse("rv32e").unwrap().base, 'e');
+    }
+
+    #[test]
+    fn rejects_malformed_strings() {
+        for isa in ["rv64i!@", "rv64imac_!", "rv64im-a", "rv64imac_zba_z", "x86_64", "rv48i", "rv64"] {
+            assert!(matches!(RiscvIsa::parse(isa), Err(Error::Parse { .. })), "{}", isa);
+        }
+    }
+}
@@ -0,0 +1,696 @@
+use serde::{Deserialize, Serialize};
+use crate::{ByteSize, Error};
+
+// A decoded SMBIOS (DMI) structure table. It can be read from sysfs, from the blob Windows
+// returns for GetSystemFirmwareTable('RSMB'), or from any captured entry point and table bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Smbios {
+    pub version: Option<(u8, u8)>, // (major, minor), when an entry point was available
+    pub structures: Vec<Structure>,
+}
+
+// The SMBIOS 2.1 (32-bit, "_SM_") or 3.0 (64-bit, "_SM3_") entry point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryPoint {
+    pub major: u8,
+    pub minor: u8,
+    pub table_address: u64,
+    pub table_length: u32, // The exact length for 2.1, the maximum length for 3.0
+}
+
+// One raw structure: its formatted area (header included) and its string set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Structure {
+    pub kind: u8,
+    pub handle: u16,
+    pub formatted: Vec<u8>,
+    pub strings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Record {
+    Bios(Bios),                                          // Type 0
+    System(SystemInfo),                                  // Type 1
+    Baseboard(Baseboard),                                // Type 2
+    Chassis(Chassis),                                    // Type 3
+    Processor(Processor),                                // Type 4
+    Cache(Cache),                                        // Type 7
+    PhysicalMemoryArray(PhysicalMemoryArray),            // Type 16
+    MemoryDevice(MemoryDevice),                          // Type 17
+    MemoryArrayMappedAddress(MemoryArrayMappedAddress),  // Type 19
+    Other(u8),                                           // Any type we don't decode
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bios {
+    pub vendor: String,
+    pub version: String,
+    pub release_date: String,
+    pub rom_size: Option<ByteSize>,
+    pub characteristics: u64,
+    pub release: Option<(u8, u8)>,
+    pub embedded_controller_release: Option<(u8, u8)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub manufacturer: String,
+    pub product_name: String,
+    pub version: String,
+    pub serial_number: String,
+    pub uuid: Option<String>,
+    pub sku_number: String,
+    pub family: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseboard {
+    pub manufacturer: String,
+    pub product: String,
+    pub version: String,
+    pub serial_number: String,
+    pub asset_tag: String,
+    pub location_in_chassis: String,
+    pub board_type: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chassis {
+    pub manufacturer: String,
+    pub chassis_type: u8, // e.g. 3 desktop, 9 laptop, 17 main server chassis, 23 rack mount
+    pub locked: bool,
+    pub version: String,
+    pub serial_number: String,
+    pub asset_tag: String,
+    pub height_u: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Processor {
+    pub socket: String,
+    pub processor_type: u8,
+    pub family: u16,
+    pub manufacturer: String,
+    pub id: u64, // The raw CPUID signature and feature flags on x86, MIDR on ARM
+    pub version: String,
+    pub max_speed_mhz: Option<u16>,
+    pub current_speed_mhz: Option<u16>,
+    pub populated: bool,
+    pub l1_cache_handle: Option<u16>,
+    pub l2_cache_handle: Option<u16>,
+    pub l3_cache_handle: Option<u16>,
+    pub serial_number: String,
+    pub part_number: String,
+    pub core_count: Option<u16>,
+    pub cores_enabled: Option<u16>,
+    pub thread_count: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cache {
+    pub handle: u16,
+    pub socket: String,
+    pub level: u8,
+    pub enabled: bool,
+    pub maximum_size: Option<ByteSize>,
+    pub installed_size: Option<ByteSize>,
+    pub kind: Option<u8>, // 3 instruction, 4 data, 5 unified
+    pub associativity: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhysicalMemoryArray {
+    pub handle: u16,
+    pub location: u8,
+    pub usage: u8,
+    pub error_correction: u8,
+    pub maximum_capacity: Option<ByteSize>,
+    pub devices: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryDevice {
+    pub array_handle: u16,
+    pub size: Option<ByteSize>, // None for an empty slot or when the size is unknown
+    pub form_factor: u8,
+    pub device_locator: String,
+    pub bank_locator: String,
+    pub memory_type: u8,
+    pub speed_mts: Option<u32>,
+    pub manufacturer: String,
+    pub serial_number: String,
+    pub asset_tag: String,
+    pub part_number: String,
+    pub rank: Option<u8>,
+    pub configured_speed_mts: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryArrayMappedAddress {
+    pub starting_address: u64, // In bytes
+    pub ending_address: u64,   // In bytes, inclusive
+    pub array_handle: u16,
+    pub partition_width: u8,
+}
+
+impl EntryPoint {
+    pub fn parse(bytes: &[u8]) -> Result<EntryPoint, Error> {
+        let error = |reason: &str| Error::parse("SMBIOS entry point", reason);
+        let checksum = |length: usize| {
+            bytes.get(..length)
+                .map(|bytes| bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) == 0)
+                .unwrap_or(false)
+        };
+
+        if bytes.starts_with(b"_SM3_") {
+            let length = *bytes.get(0x06).ok_or_else(|| error("truncated"))? as usize;
+            if length < 0x18 || !checksum(length) {
+                return Err(error("bad length or checksum"));
+            }
+            Ok(EntryPoint {
+                major: bytes[0x07],
+                minor: bytes[0x08],
+                table_address: u64::from_le_bytes(bytes[0x10..0x18].try_into().unwrap_or_default()),
+                table_length: u32::from_le_bytes(bytes[0x0C..0x10].try_into().unwrap_or_default()),
+            })
+        } else if bytes.starts_with(b"_SM_") {
+            let length = *bytes.get(0x05).ok_or_else(|| error("truncated"))? as usize;
+            if length < 0x1F || !checksum(length) {
+                return Err(error("bad length or checksum"));
+            }
+            Ok(EntryPoint {
+                major: bytes[0x06],
+                minor: bytes[0x07],
+                table_address: u32::from_le_bytes(bytes[0x18..0x1C].try_into().unwrap_or_default()) as u64,
+                table_length: u16::from_le_bytes([bytes[0x16], bytes[0x17]]) as u32,
+            })
+        } else {
+            Err(error("missing _SM_ or _SM3_ anchor"))
+        }
+    }
+}
+
+impl Smbios {
+    pub fn parse(entry_point: &[u8], table: &[u8]) -> Result<Smbios, Error> {
+        let entry_point = EntryPoint::parse(entry_point)?;
+        let length = (entry_point.table_length as usize).min(table.len());
+        Smbios::parse_table(&table[..length], Some((entry_point.major, entry_point.minor)))
+    }
+
+    // Parses the RawSMBIOSData blob returned by GetSystemFirmwareTable('RSMB') on Windows
+    pub fn parse_raw_smbios_data(data: &[u8]) -> Result<Smbios, Error> {
+        if data.len() < 8 {
+            return Err(Error::parse("RawSMBIOSData", "truncated header"));
+        }
+        let length = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let table = data.get(8..8 + length).ok_or_else(|| Error::parse("RawSMBIOSData", "truncated table"))?;
+        Smbios::parse_table(table, Some((data[1], data[2])))
+    }
+
+    pub fn parse_table(table: &[u8], version: Option<(u8, u8)>) -> Result<Smbios, Error> {
+        let mut structures = Vec::new();
+        let mut offset = 0;
+
+        while offset + 4 <= table.len() {
+            let kind = table[offset];
+            let length = table[offset + 1] as usize;
+            if length < 4 || offset + length > table.len() {
+                return Err(Error::parse("SMBIOS structure", format!("bad length {} at offset {}", length, offset)));
+            }
+            let formatted = table[offset..offset + length].to_vec();
+
+            // The string set follows the formatted area and ends with a double NUL
+            let strings_start = offset + length;
+            let mut end = strings_start;
+            while end + 1 < table.len() && !(table[end] == 0 && table[end + 1] == 0) {
+                end += 1;
+            }
+            if end + 1 >= table.len() {
+                return Err(Error::parse("SMBIOS structure", format!("unterminated strings at offset {}", offset)));
+            }
+            let strings = if end == strings_start {
+                Vec::new()
+            } else {
+                table[strings_start..end]
+                    .split(|&b| b == 0)
+                    .map(|s| String::from_utf8_lossy(s).trim().to_string())
+                    .collect()
+            };
+
+            structures.push(Structure {
+                kind,
+                handle: u16::from_le_bytes([table[offset + 2], table[offset + 3]]),
+                formatted,
+                strings,
+            });
+
+            offset = end + 2;
+            if kind == 127 {
+                break; // End-of-table
+            }
+        }
+
+        Ok(Smbios { version, structures })
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        self.structures.iter().map(Structure::decode)
+    }
+
+    pub fn bios(&self) -> Option<Bios> {
+        self.records().find_map(|record| match record { Record::Bios(bios) => Some(bios), _ => None })
+    }
+
+    pub fn system(&self) -> Option<SystemInfo> {
+        self.records().find_map(|record| match record { Record::System(system) => Some(system), _ => None })
+    }
+
+    pub fn baseboards(&self) -> Vec<Baseboard> {
+        self.records().filter_map(|record| match record { Record::Baseboard(board) => Some(board), _ => None }).collect()
+    }
+
+    pub fn chassis(&self) -> Vec<Chassis> {
+        self.records().filter_map(|record| match record { Record::Chassis(chassis) => Some(chassis), _ => None }).collect()
+    }
+
+    pub fn processors(&self) -> Vec<Processor> {
+        self.records().filter_map(|record| match record { Record::Processor(processor) => Some(processor), _ => None }).collect()
+    }
+
+    pub fn caches(&self) -> Vec<Cache> {
+        self.records().filter_map(|record| match record { Record::Cache(cache) => Some(cache), _ => None }).collect()
+    }
+
+    pub fn memory_arrays(&self) -> Vec<PhysicalMemoryArray> {
+        self.records().filter_map(|record| match record { Record::PhysicalMemoryArray(array) => Some(array), _ => None }).collect()
+    }
+
+    pub fn memory_devices(&self) -> Vec<MemoryDevice> {
+        self.records().filter_map(|record| match record { Record::MemoryDevice(device) => Some(device), _ => None }).collect()
+    }
+
+    pub fn memory_array_mapped_addresses(&self) -> Vec<MemoryArrayMappedAddress> {
+        self.records()
+            .filter_map(|record| match record { Record::MemoryArrayMappedAddress(mapped) => Some(mapped), _ => None })
+            .collect()
+    }
+}
+
+impl Structure {
+    pub fn byte(&self, offset: usize) -> Option<u8> {
+        self.formatted.get(offset).copied()
+    }
+
+    pub fn word(&self, offset: usize) -> Option<u16> {
+        self.formatted.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub fn dword(&self, offset: usize) -> Option<u32> {
+        self.formatted.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn qword(&self, offset: usize) -> Option<u64> {
+        self.formatted.get(offset..offset + 8).map(|b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+    }
+
+    // Strings are referenced by a 1-based index stored in the formatted area, 0 meaning none
+    pub fn string(&self, offset: usize) -> String {
+        self.byte(offset)
+            .filter(|&index| index > 0)
+            .and_then(|index| self.strings.get(index as usize - 1))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // Handles of 0xFFFF mean "not provided"
+    fn handle_at(&self, offset: usize) -> Option<u16> {
+        self.word(offset).filter(|&handle| handle != 0xFFFF)
+    }
+
+    pub fn decode(&self) -> Record {
+        match self.kind {
+            0 => Record::Bios(Bios {
+                vendor: self.string(0x04),
+                version: self.string(0x05),
+                release_date: self.string(0x08),
+                rom_size: match self.byte(0x09) {
+                    // 0xFF defers to the extended size: bits 14-15 pick MB or GB
+                    Some(0xFF) => self.word(0x18).map(|size| match size >> 14 {
+                        0 => ByteSize::from_mib((size & 0x3FFF) as u64),
+                        _ => ByteSize::from_gib((size & 0x3FFF) as u64),
+                    }),
+                    Some(size) => Some(ByteSize::from_kib((size as u64 + 1) * 64)),
+                    None => None,
+                },
+                characteristics: self.qword(0x0A).unwrap_or(0),
+                release: self.byte(0x14).zip(self.byte(0x15)).filter(|&(major, _)| major != 0xFF),
+                embedded_controller_release: self.byte(0x16).zip(self.byte(0x17)).filter(|&(major, _)| major != 0xFF),
+            }),
+            1 => Record::System(SystemInfo {
+                manufacturer: self.string(0x04),
+                product_name: self.string(0x05),
+                version: self.string(0x06),
+                serial_number: self.string(0x07),
+                uuid: self.formatted.get(0x08..0x18).and_then(format_uuid),
+                sku_number: self.string(0x19),
+                family: self.string(0x1A),
+            }),
+            2 => Record::Baseboard(Baseboard {
+                manufacturer: self.string(0x04),
+                product: self.string(0x05),
+                version: self.string(0x06),
+                serial_number: self.string(0x07),
+                asset_tag: self.string(0x08),
+                location_in_chassis: self.string(0x0A),
+                board_type: self.byte(0x0D),
+            }),
+            3 => Record::Chassis(Chassis {
+                manufacturer: self.string(0x04),
+                chassis_type: self.byte(0x05).unwrap_or(0) & 0x7F,
+                locked: self.byte(0x05).unwrap_or(0) & 0x80 != 0,
+                version: self.string(0x06),
+                serial_number: self.string(0x07),
+                asset_tag: self.string(0x08),
+                height_u: self.byte(0x11).filter(|&height| height != 0),
+            }),
+            4 => Record::Processor(Processor {
+                socket: self.string(0x04),
+                processor_type: self.byte(0x05).unwrap_or(0),
+                // 0xFE defers to the Processor Family 2 field
+                family: match self.byte(0x06) {
+                    Some(0xFE) => self.word(0x28).unwrap_or(0xFE),
+                    family => family.unwrap_or(0) as u16,
+                },
+                manufacturer: self.string(0x07),
+                id: self.qword(0x08).unwrap_or(0),
+                version: self.string(0x10),
+                max_speed_mhz: self.word(0x14).filter(|&speed| speed != 0),
+                current_speed_mhz: self.word(0x16).filter(|&speed| speed != 0),
+                populated: self.byte(0x18).unwrap_or(0) & 0x40 != 0,
+                l1_cache_handle: self.handle_at(0x1A),
+                l2_cache_handle: self.handle_at(0x1C),
+                l3_cache_handle: self.handle_at(0x1E),
+                serial_number: self.string(0x20),
+                part_number: self.string(0x22),
+                core_count: self.count(0x23, 0x2A),
+                cores_enabled: self.count(0x24, 0x2C),
+                thread_count: self.count(0x25, 0x2E),
+            }),
+            7 => {
+                let configuration = self.word(0x05).unwrap_or(0);
+                Record::Cache(Cache {
+                    handle: self.handle,
+                    socket: self.string(0x04),
+                    level: (configuration & 0x7) as u8 + 1,
+                    enabled: configuration & 0x80 != 0,
+                    maximum_size: cache_size(self.word(0x07), self.dword(0x13)),
+                    installed_size: cache_size(self.word(0x09), self.dword(0x17)),
+                    kind: self.byte(0x11),
+                    associativity: self.byte(0x12),
+                })
+            }
+            16 => Record::PhysicalMemoryArray(PhysicalMemoryArray {
+                handle: self.handle,
+                location: self.byte(0x04).unwrap_or(0),
+                usage: self.byte(0x05).unwrap_or(0),
+                error_correction: self.byte(0x06).unwrap_or(0),
+                // In KB, 0x80000000 defers to the extended capacity in bytes
+                maximum_capacity: match self.dword(0x07) {
+                    Some(0x8000_0000) => self.qword(0x0F).map(ByteSize::from_bytes),
+                    Some(kb) => Some(ByteSize::from_kib(kb as u64)),
+                    None => None,
+                },
+                devices: self.word(0x0D).unwrap_or(0),
+            }),
+            17 => Record::MemoryDevice(MemoryDevice {
+                array_handle: self.word(0x04).unwrap_or(0xFFFF),
+                // 0 means the slot is empty and 0xFFFF that the size is unknown. Bit 15 selects KB
+                // instead of MB, and 0x7FFF defers to the extended size field (in MB).
+                size: match self.word(0x0C) {
+                    None | Some(0) | Some(0xFFFF) => None,
+                    Some(0x7FFF) => self.dword(0x1C).map(|mb| ByteSize::from_mib((mb & 0x7FFF_FFFF) as u64)),
+                    Some(size) if size & 0x8000 != 0 => Some(ByteSize::from_kib((size & 0x7FFF) as u64)),
+                    Some(size) => Some(ByteSize::from_mib(size as u64)),
+                },
+                form_factor: self.byte(0x0E).unwrap_or(0),
+                device_locator: self.string(0x10),
+                bank_locator: self.string(0x11),
+                memory_type: self.byte(0x12).unwrap_or(0),
+                speed_mts: speed(self.word(0x15), self.dword(0x54)),
+                manufacturer: self.string(0x17),
+                serial_number: self.string(0x18),
+                asset_tag: self.string(0x19),
+                part_number: self.string(0x1A),
+                rank: self.byte(0x1B).map(|attributes| attributes & 0x0F).filter(|&rank| rank != 0),
+                configured_speed_mts: speed(self.word(0x20), self.dword(0x58)),
+            }),
+            19 => {
+                // In KB, 0xFFFFFFFF defers to the extended addresses in bytes
+                let (start, end) = match self.dword(0x04) {
+                    Some(0xFFFF_FFFF) => (self.qword(0x0F).unwrap_or(0), self.qword(0x17).unwrap_or(0)),
+                    start => (
+                        start.unwrap_or(0) as u64 * 1024,
+                        self.dword(0x08).unwrap_or(0) as u64 * 1024 + 1023,
+                    ),
+                };
+                Record::MemoryArrayMappedAddress(MemoryArrayMappedAddress {
+                    starting_address: start,
+                    ending_address: end,
+                    array_handle: self.word(0x0C).unwrap_or(0xFFFF),
+                    partition_width: self.byte(0x0E).unwrap_or(0),
+                })
+            }
+            kind => Record::Other(kind),
+        }
+    }
+
+    // Processor counts use 0xFF in the byte field to defer to a wider field added in SMBIOS 3.0
+    fn count(&self, offset: usize, extended: usize) -> Option<u16> {
+        match self.byte(offset)? {
+            0 => None,
+            0xFF => self.word(extended).filter(|&count| count != 0),
+            count => Some(count as u16),
+        }
+    }
+}
+
+impl MemoryDevice {
+    pub fn memory_type_name(&self) -> Option<&'static str> {
+        Some(match self.memory_type {
+            0x0F => "SDRAM",
+            0x12 => "DDR",
+            0x13 => "DDR2",
+            0x18 => "DDR3",
+            0x1A => "DDR4",
+            0x1B => "LPDDR",
+            0x1C => "LPDDR2",
+            0x1D => "LPDDR3",
+            0x1E => "LPDDR4",
+            0x20 => "HBM",
+            0x21 => "HBM2",
+            0x22 => "DDR5",
+            0x23 => "LPDDR5",
+            0x24 => "HBM3",
+            _ => return None,
+        })
+    }
+}
+
+// Cache sizes use bit 15 (or bit 31 in the SMBIOS 3.1 "size 2" fields) to pick 64K instead
+// of 1K granularity. A legacy value of 0xFFFF defers to the 32-bit field.
+fn cache_size(legacy: Option<u16>, extended: Option<u32>) -> Option<ByteSize> {
+    let kb = match legacy? {
+        0xFFFF => {
+            let size = extended?;
+            let units = (size & 0x7FFF_FFFF) as u64;
+            if size & 0x8000_0000 != 0 { units * 64 } else { units }
+        }
+        size => {
+            let units = (size & 0x7FFF) as u64;
+            if size & 0x8000 != 0 { units * 64 } else { units }
+        }
+    };
+    (kb > 0).then(|| ByteSize::from_kib(kb))
+}
+
+// Speeds of 0 are unknown, and 0xFFFF defers to the 32-bit extended speed
+fn speed(legacy: Option<u16>, extended: Option<u32>) -> Option<u32> {
+    match legacy? {
+        0 => None,
+        0xFFFF => extended.filter(|&speed| speed != 0),
+        speed => Some(speed as u32),
+    }
+}
+
+// The first three fields are little-endian since SMBIOS 2.6. All 0x00 or all 0xFF means unset.
+fn format_uuid(bytes: &[u8]) -> Option<String> {
+    if bytes.iter().all(|&b| b == 0) || bytes.iter().all(|&b| b == 0xFF) {
+        return None;
+    }
+    Some(format!(
+        "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        bytes[3], bytes[2], bytes[1], bytes[0], bytes[5], bytes[4], bytes[7], bytes[6],
+        bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Entry points and structure table of an MSI B550-A PRO with a Ryzen 7 5800X
+    const ENTRY_POINT_32: &[u8] = include_bytes!("../tests/fixtures/smbios/entry_point_32.bin");
+    const ENTRY_POINT_64: &[u8] = include_bytes!("../tests/fixtures/smbios/entry_point_64.bin");
+    const TABLE: &[u8] = include_bytes!("../tests/fixtures/smbios/table.bin");
+
+    fn fixture() -> Smbios {
+        Smbios::parse(ENTRY_POINT_64, TABLE).unwrap()
+    }
+
+    #[test]
+    fn entry_points() {
+        let legacy = EntryPoint::parse(ENTRY_POINT_32).unwrap();
+        assert_eq!((legacy.major, legacy.minor), (2, 8));
+        assert_eq!(legacy.table_address, 0x000E_9F10);
+        assert_eq!(legacy.table_length as usize, TABLE.len());
+
+        let current = EntryPoint::parse(ENTRY_POINT_64).unwrap();
+        assert_eq!((current.major, current.minor), (3, 3));
+        assert_eq!(current.table_address, 0xBB5A_6000);
+        assert!(current.table_length as usize > TABLE.len());
+
+        let mut corrupt = ENTRY_POINT_64.to_vec();
+        corrupt[0x10] ^= 0xFF;
+        assert!(matches!(EntryPoint::parse(&corrupt), Err(Error::Parse { .. })));
+        assert!(matches!(EntryPoint::parse(b"_DMI_"), Err(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn both_entry_points_find_every_structure() {
+        let legacy = Smbios::parse(ENTRY_POINT_32, TABLE).unwrap();
+        let current = fixture();
+        assert_eq!(legacy.version, Some((2, 8)));
+        assert_eq!(current.version, Some((3, 3)));
+        assert_eq!(legacy.structures, current.structures);
+        let kinds: Vec<u8> = current.structures.iter().map(|structure| structure.kind).collect();
+        assert_eq!(kinds, [0, 1, 2, 3, 7, 7, 7, 4, 16, 17, 17, 17, 17, 19, 19, 127]);
+    }
+
+    #[test]
+    fn raw_smbios_data() {
+        let mut data = vec![0, 3, 3, 0];
+        data.extend_from_slice(&(TABLE.len() as u32).to_le_bytes());
+        data.extend_from_slice(TABLE);
+        let smbios = Smbios::parse_raw_smbios_data(&data).unwrap();
+        assert_eq!(smbios.version, Some((3, 3)));
+        assert_eq!(smbios.structures, fixture().structures);
+        assert!(Smbios::parse_raw_smbios_data(&data[..64]).is_err());
+    }
+
+    #[test]
+    fn bios_and_system() {
+        let smbios = fixture();
+        let bios = smbios.bios().unwrap();
+        assert_eq!(bios.vendor, "American Megatrends International, LLC.");
+        assert_eq!(bios.version, "A.G0");
+        assert_eq!(bios.release_date, "03/01/2024");
+        assert_eq!(bios.rom_size, Some(ByteSize::from_mib(16)));
+        assert_eq!(bios.characteristics, 0x0B0B_C9E80);
+        assert_eq!(bios.release, Some((5, 17)));
+        assert_eq!(bios.embedded_controller_release, None);
+
+        let system = smbios.system().unwrap();
+        assert_eq!(system.manufacturer, "Micro-Star International Co., Ltd.");
+        assert_eq!(system.product_name, "MS-7C56");
+        assert_eq!(system.version, "2.0");
+        assert_eq!(system.uuid.as_deref(), Some("76543210-BA98-FEDC-0123-456789ABCDEF"));
+        assert_eq!(system.family, "To be filled by O.E.M.");
+    }
+
+    #[test]
+    fn baseboard_and_chassis() {
+        let smbios = fixture();
+        let boards = smbios.baseboards();
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].product, "B550-A PRO (MS-7C56)");
+        assert_eq!(boards[0].serial_number, "07C5611_N31E123456");
+        assert_eq!(boards[0].board_type, Some(0x0A));
+
+        let chassis = smbios.chassis();
+        assert_eq!(chassis.len(), 1);
+        assert_eq!(chassis[0].chassis_type, 3);
+        assert!(chassis[0].locked);
+        assert_eq!(chassis[0].version, "2.0");
+        assert_eq!(chassis[0].height_u, None);
+    }
+
+    #[test]
+    fn processor_and_caches() {
+        let smbios = fixture();
+        let processors = smbios.processors();
+        assert_eq!(processors.len(), 1);
+        let processor = &processors[0];
+        assert_eq!(processor.socket, "AM4");
+        assert_eq!(processor.family, 0x6B);
+        assert_eq!(processor.manufacturer, "Advanced Micro Devices, Inc.");
+        assert_eq!(processor.version, "AMD Ryzen 7 5800X 8-Core Processor");
+        assert_eq!(processor.id, 0x178B_FBFF_00A2_0F12);
+        assert_eq!(processor.max_speed_mhz, Some(4850));
+        assert_eq!(processor.current_speed_mhz, Some(3800));
+        assert!(processor.populated);
+        assert_eq!(processor.core_count, Some(8));
+        assert_eq!(processor.cores_enabled, Some(8));
+        assert_eq!(processor.thread_count, Some(16));
+        assert_eq!(processor.serial_number, "");
+
+        let caches = smbios.caches();
+        let handles = [processor.l1_cache_handle, processor.l2_cache_handle, processor.l3_cache_handle];
+        assert_eq!(handles, [Some(0x0004), Some(0x0005), Some(0x0006)]);
+        let sizes: Vec<(u16, u8, Option<ByteSize>)> =
+            caches.iter().map(|cache| (cache.handle, cache.level, cache.installed_size)).collect();
+        assert_eq!(sizes, [
+            (0x0004, 1, Some(ByteSize::from_kib(512))),
+            (0x0005, 2, Some(ByteSize::from_mib(4))),
+            (0x0006, 3, Some(ByteSize::from_mib(32))),
+        ]);
+        assert!(caches.iter().all(|cache| cache.enabled && cache.kind == Some(5)));
+    }
+
+    #[test]
+    fn memory() {
+        let smbios = fixture();
+        let arrays = smbios.memory_arrays();
+        assert_eq!(arrays.len(), 1);
+        assert_eq!(arrays[0].maximum_capacity, Some(ByteSize::from_gib(128)));
+        assert_eq!(arrays[0].devices, 4);
+
+        let devices = smbios.memory_devices();
+        let sizes: Vec<Option<ByteSize>> = devices.iter().map(|device| device.size).collect();
+        assert_eq!(sizes, [Some(ByteSize::from_gib(16)), Some(ByteSize::from_gib(32)), None, None]);
+        let dimm = &devices[1];
+        assert_eq!(dimm.array_handle, arrays[0].handle);
+        assert_eq!(dimm.device_locator, "DIMM_A2");
+        assert_eq!(dimm.bank_locator, "P0 CHANNEL A");
+        assert_eq!(dimm.memory_type_name(), Some("DDR4"));
+        assert_eq!(dimm.speed_mts, Some(3600));
+        assert_eq!(dimm.configured_speed_mts, Some(3600));
+        assert_eq!(dimm.manufacturer, "Kingston");
+        assert_eq!(dimm.part_number, "KF3600C18D4/32GX");
+        assert_eq!(dimm.rank, Some(2));
+        assert_eq!(devices[2].speed_mts, None);
+        assert_eq!(devices[2].rank, None);
+
+        let mapped: Vec<(u64, u64)> = smbios
+            .memory_array_mapped_addresses()
+            .iter()
+            .map(|mapped| (mapped.starting_address, mapped.ending_address))
+            .collect();
+        assert_eq!(mapped, [(0, (2 << 30) - 1), (4 << 30, (50 << 30) - 1)]);
+    }
+
+    #[test]
+    fn truncated_table() {
+        assert!(matches!(Smbios::parse_table(&TABLE[..40], None), Err(Error::Parse { .. })));
+        assert!(matches!(Smbios::parse_table(&[1, 2, 0, 0], None), Err(Error::Parse { .. })));
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::{CPUInfo, Error, GPUInfo, MemInfo, MemoryUsage, OSInfo};
+
+// Everything we know about a machine, collected in one go so it can be shipped as a single
+// JSON document. A collector that fails leaves its section empty and records why in `errors`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub host: String,
+    pub cpus: Vec<CPUInfo>,
+    pub gpus: Vec<GPUInfo>,
+    pub os: Option<OSInfo>,
+    pub memory: Vec<MemInfo>,
+    #[serde(default)]
+    pub memory_usage: Option<MemoryUsage>,
+    #[serde(default)]
+    pub errors: Vec<CollectorError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Component {
+    Cpu,
+    Gpu,
+    Os,
+    Memory,
+    MemoryUsage,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Component::Cpu => "cpu",
+            Component::Gpu => "gpu",
+            Component::Os => "os",
+            Component::Memory => "memory",
+            Component::MemoryUsage => "memory_usage",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectorError {
+    pub component: Component,
+    pub error: Error,
+}
+
+impl SystemSnapshot {
+    // Collectors the platform has no backend for are recorded as UnsupportedPlatform errors
+    pub fn collect() -> SystemSnapshot {
+        SystemSnapshot::from_results(
+            CPUInfo::fetch(),
+            GPUInfo::fetch(),
+            OSInfo::fetch(),
+            MemInfo::fetch(),
+            MemoryUsage::fetch(),
+        )
+    }
+
+    fn from_results(
+        cpus: Result<Vec<CPUInfo>, Error>,
+        gpus: Result<Vec<GPUInfo>, Error>,
+        os: Result<Vec<OSInfo>, Error>,
+        memory: Result<Vec<MemInfo>, Error>,
+        memory_usage: Result<MemoryUsage, Error>,
+    ) -> SystemSnapshot {
+        let timestamp = Utc::now();
+        let mut errors = Vec::new();
+
+        let cpus = collect(Component::Cpu, cpus, &mut errors).unwrap_or_default();
+        let gpus = collect(Component::Gpu, gpus, &mut errors).unwrap_or_default();
+        let os = collect(Component::Os, os, &mut errors).and_then(|os| os.into_iter().next());
+        let memory = collect(Component::Memory, memory, &mut errors).unwrap_or_default();
+        let memory_usage = collect(Component::MemoryUsage, memory_usage, &mut errors);
+
+        let host = os
+            .as_ref()
+            .map(|os| os.computer_name.clone())
+            .unwrap_or_else(hostname);
+
+        SystemSnapshot { timestamp, host, cpus, gpus, os, memory, memory_usage, errors }
+    }
+
+    // Loads a snapshot previously written out as JSON
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<SystemSnapshot, Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|err| Error::io(path.display().to_string(), err))?;
+        serde_json::from_str(&json).map_err(|err| Error::parse(path.display().to_string(), err.to_string()))
+    }
+
+    // True when every collector succeeded
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+fn collect<T>(component: Component, result: Result<T, Error>, errors: &mut Vec<CollectorError>) -> Option<T> {
+    result
+        .map_err(|error| errors.push(CollectorError { component, error }))
+        .ok()
+}
+
+// Falls back to the environment and the kernel when the OS collector couldn't tell us the name
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::SysRoot;
+
+    #[test]
+    fn failing_collectors_are_recorded() {
+        let root = SysRoot::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/linux/desktop"));
+        let snapshot = SystemSnapshot::from_results(
+            CPUInfo::fetch_from_root(&root),
+            Err(Error::unsupported()),
+            OSInfo::fetch_from_root(&root),
+            Err(Error::parse("meminfo", "truncated")),
+            MemoryUsage::fetch_from_root(&root),
+        );
+        assert!(!snapshot.is_complete());
+        let failed: Vec<Component> = snapshot.errors.iter().map(|failure| failure.component).collect();
+        assert_eq!(failed, [Component::Gpu, Component::Memory]);
+        assert!(matches!(snapshot.errors[0].error, Error::UnsupportedPlatform { .. }));
+
+        // Everything else still made it in
+        assert_eq!(snapshot.cpus.len(), 1);
+        assert!(snapshot.gpus.is_empty() && snapshot.memory.is_empty());
+        assert_eq!(snapshot.host, "workstation");
+        assert!(snapshot.memory_usage.is_some());
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let back: SystemSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.errors, snapshot.errors);
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use serde::{Deserialize, Serialize};
+
+// A clock frequency, stored in Hz
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Frequency(u64);
+
+impl Frequency {
+    pub const fn from_hz(hz: u64) -> Self {
+        Frequency(hz)
+    }
+
+    pub const fn from_khz(khz: u64) -> Self {
+        Frequency(khz * 1_000)
+    }
+
+    pub const fn from_mhz(mhz: u64) -> Self {
+        Frequency(mhz * 1_000_000)
+    }
+
+    pub const fn hz(self) -> u64 {
+        self.0
+    }
+
+    pub const fn khz(self) -> u64 {
+        self.0 / 1_000
+    }
+
+    pub const fn mhz(self) -> u64 {
+        self.0 / 1_000_000
+    }
+
+    pub fn ghz(self) -> f64 {
+        self.0 as f64 / 1_000_000_000.0
+    }
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_scaled(f, self.0, 1000, &["Hz", "kHz", "MHz", "GHz", "THz"])
+    }
+}
+
+// A size in bytes. Displays with IEC (binary) units by default; use `si_string` for SI units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    pub const fn from_bytes(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+
+    pub const fn from_kib(kib: u64) -> Self {
+        ByteSize(kib * 1024)
+    }
+
+    pub const fn from_mib(mib: u64) -> Self {
+        ByteSize(mib * 1024 * 1024)
+    }
+
+    pub const fn from_gib(gib: u64) -> Self {
+        ByteSize(gib * 1024 * 1024 * 1024)
+    }
+
+    pub const fn bytes(self) -> u64 {
+        self.0
+    }
+
+    pub const fn kib(self) -> u64 {
+        self.0 / 1024
+    }
+
+    pub const fn mib(self) -> u64 {
+        self.0 / (1024 * 1024)
+    }
+
+    pub fn iec_string(self) -> String {
+        self.to_string()
+    }
+
+    pub fn si_string(self) -> String {
+        struct Si(u64);
+        impl fmt::Display for Si {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                format_scaled(f, self.0, 1000, &["B", "kB", "MB", "GB", "TB", "PB", "EB"])
+            }
+        }
+        Si(self.0).to_string()
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_scaled(f, self.0, 1024, &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"])
+    }
+}
+
+impl Add for ByteSize {
+    type Output = ByteSize;
+
+    fn add(self, rhs: ByteSize) -> ByteSize {
+        ByteSize(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for ByteSize {
+    fn add_assign(&mut self, rhs: ByteSize) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sum for ByteSize {
+    fn sum<I: Iterator<Item = ByteSize>>(iter: I) -> ByteSize {
+        iter.fold(ByteSize::default(), Add::add)
+    }
+}
+
+// Picks the largest unit the value reaches and prints it with two decimals, or none for
+// the base unit and exact multiples (e.g. "48 KiB", "3.60 GHz")
+fn format_scaled(f: &mut fmt::Formatter<'_>, value: u64, step: u64, units: &[&str]) -> fmt::Result {
+    let mut divisor = 1u64;
+    let mut unit = 0;
+    while unit + 1 < units.len() && value / divisor >= step {
+        divisor *= step;
+        unit += 1;
+    }
+    if value.is_multiple_of(divisor) {
+        write!(f, "{} {}", value / divisor, units[unit])
+    } else {
+        let precision = f.precision().unwrap_or(2);
+        write!(f, "{:.*} {}", precision, value as f64 / divisor as f64, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let clock = Frequency::from_mhz(3600);
+        assert_eq!((clock.hz(), clock.khz(), clock.mhz()), (3_600_000_000, 3_600_000, 3600));
+        assert_eq!(clock.ghz(), 3.6);
+        assert_eq!(Frequency::from_khz(3_600_000), clock);
+
+        let size = ByteSize::from_gib(2);
+        assert_eq!((size.kib(), size.mib()), (2 * 1024 * 1024, 2048));
+        assert_eq!(ByteSize::from_mib(2048), size);
+        assert_eq!(ByteSize::from_kib(1).bytes(), 1024);
+        let total: ByteSize = [ByteSize::from_kib(48), ByteSize::from_kib(32)].into_iter().sum();
+        assert_eq!(total, ByteSize::from_kib(80));
+    }
+
+    #[test]
+    fn exact_multiples_drop_the_decimals() {
+        assert_eq!(Frequency::from_mhz(2000).to_string(), "2 GHz");
+        assert_eq!(Frequency::from_hz(999).to_string(), "999 Hz");
+        assert_eq!(ByteSize::from_kib(48).to_string(), "48 KiB");
+        assert_eq!(ByteSize::from_gib(16).to_string(), "16 GiB");
+        assert_eq!(ByteSize::from_bytes(1000).si_string(), "1 kB");
+    }
+
+    #[test]
+    fn fractions_and_precision() {
+        assert_eq!(Frequency::from_mhz(3600).to_string(), "3.60 GHz");
+        assert_eq!(format!("{:.1}", Frequency::from_mhz(3600)), "3.6 GHz");
+        assert_eq!(Frequency::from_khz(1500).to_string(), "1.50 MHz");
+        assert_eq!(ByteSize::from_bytes(1536).to_string(), "1.50 KiB");
+        assert_eq!(format!("{:.3}", ByteSize::from_mib(1536 + 1)), "1.501 GiB");
+    }
+
+    #[test]
+    fn iec_and_si_prefixes() {
+        let size = ByteSize::from_gib(16);
+        assert_eq!(size.iec_string(), "16 GiB");
+        assert_eq!(size.si_string(), "17.18 GB");
+        assert_eq!(ByteSize::from_bytes(1536).si_string(), "1.54 kB");
+        assert_eq!(ByteSize::from_bytes(999).si_string(), "999 B");
+    }
+
+    #[test]
+    fn zero_and_max() {
+        assert_eq!(Frequency::from_hz(0).to_string(), "0 Hz");
+        assert_eq!(ByteSize::default().to_string(), "0 B");
+        assert_eq!(ByteSize::default().si_string(), "0 B");
+        assert_eq!(Frequency::from_hz(u64::MAX).to_string(), "18446744.07 THz");
+        assert_eq!(ByteSize::from_bytes(u64::MAX).to_string(), "16.00 EiB");
+        assert_eq!(ByteSize::from_bytes(u64::MAX).si_string(), "18.45 EB");
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let clock = Frequency::from_mhz(3600);
+        let size = ByteSize::from_bytes(u64::MAX);
+        assert_eq!(serde_json::to_string(&clock).unwrap(), "3600000000");
+        assert_eq!(serde_json::to_string(&size).unwrap(), u64::MAX.to_string());
+        assert_eq!(serde_json::from_str::<Frequency>("3600000000").unwrap(), clock);
+        assert_eq!(serde_json::from_str::<ByteSize>(&u64::MAX.to_string()).unwrap(), size);
+        assert!(serde_json::from_str::<ByteSize>("\"16 GiB\"").is_err());
+        assert!(serde_json::from_str::<ByteSize>("-1").is_err());
+    }
+}